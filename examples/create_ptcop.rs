@@ -0,0 +1,92 @@
+//! Example of creating a project from scratch.
+
+use ptcrab::data::WriteTo;
+use ptcrab::project::{Event, EventKind, Note, Position, Project, Unit};
+use ptcrab::ptvoice::{PtvEnvelope, PtvUnit, PtvWave, Ptvoice};
+use ptcrab::Key;
+
+use anyhow::Result as AnyResult;
+use std::env::args;
+use std::fs::File;
+
+//--------------------------------------------------------------------------------------------------
+
+fn main() -> AnyResult<()> {
+    let mut project = Project::default();
+    project.master.tempo = 140.;
+    project.set_name_text(Some("ptcrab example".into()))?;
+
+    // Add voices for the units to play.
+    //----------------------------------------------------------------------------------------------
+    let lead = project.add_voice(Ptvoice::new(Box::new([PtvUnit {
+        wave: Some(PtvWave::oscillator_from_pairs(Box::new([
+            (1, 128),
+            (3, 32),
+        ]))),
+        envelope: Some(PtvEnvelope::new(Box::new([(10, 128)]), 80)),
+        ..Default::default()
+    }])))?;
+    let bass = project.add_voice(Ptvoice::new(Box::new([PtvUnit {
+        wave: Some(PtvWave::coordinate_from_points(Box::new([
+            (0, 0),
+            (0, 64),
+            (255, -64),
+        ]))),
+        envelope: Some(PtvEnvelope::new(Box::new([(1, 128)]), 20)),
+        ..Default::default()
+    }])))?;
+    project.voices[lead].set_name_text(Some("lead".into()))?;
+    project.voices[bass].set_name_text(Some("bass".into()))?;
+
+    // Add units, each of which plays one note at a time.
+    //----------------------------------------------------------------------------------------------
+    let mut melody = Unit::default();
+    melody.set_name_text(Some("melody".into()))?;
+    let melody = project.add_unit(melody)?;
+    let mut bassline = Unit::default();
+    bassline.set_name_text(Some("bassline".into()))?;
+    let bassline = project.add_unit(bassline)?;
+
+    // Write notes at positions given in measures, beats & ticks.
+    //----------------------------------------------------------------------------------------------
+    let timing = project.master.timing(44100);
+    let eighth = timing.beat_clock() / 2;
+    let note = |measure, beat, tick, length, semitones: i32, voice| Note {
+        voice,
+        ..Note::new(
+            timing.clock_at(Position::new(measure, beat, tick)),
+            length,
+            Key::from_a4_offset(semitones * 256),
+        )
+    };
+
+    let melody_notes: Vec<_> = [0, 3, 7, 12, 10, 7, 3, 5]
+        .into_iter()
+        .enumerate()
+        .map(|(index, semitones)| {
+            let (beat, tick) = ((index as i32) / 2, (index as i32 % 2) * eighth);
+            note(0, beat, tick, eighth, semitones, lead)
+        })
+        .collect();
+    project.add_notes(melody, &melody_notes)?;
+
+    let bass_notes: Vec<_> = (0..4)
+        .map(|beat| note(0, beat, 0, eighth, -24, bass))
+        .collect();
+    project.add_notes(bassline, &bass_notes)?;
+
+    // Controller events can be added at any clock.
+    project.add_event(Event::new(0, bassline, EventKind::Volume(80.into())))?;
+
+    //----------------------------------------------------------------------------------------------
+
+    // If a filename was given, write the project to the output file. Otherwise, just print it.
+    if let Some(filename) = args().nth(1) {
+        let mut file = File::create(filename)?;
+        project.write_to(&mut file)?;
+    } else {
+        println!("{:?}", project);
+    }
+
+    Ok(())
+}
@@ -40,7 +40,7 @@ impl FromReadVar<Self> for _Num32_ {
             }
         }
 
-        Ok(result.to_le_bytes()).map(Self::from_le_bytes)
+        Ok(Self::from_le_bytes(result.to_le_bytes()))
     }
 }
 
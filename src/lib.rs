@@ -6,12 +6,11 @@
 //! - Support for generic Wasm targets
 //! - I/O using standard [`Read`](std::io::Read) & [`Write`](std::io::Write) traits
 //! - Ptvoice data manipulation
-//!
-//! ### Not yet implemented
-//!
 //! - Ptnoise data manipulation
 //! - Project (ptcop/pttune) data manipulation
-//! - Audio rendering/playback
+//! - Project editing, with unit & voice indices remapped automatically
+//! - Audio rendering, with Ogg Vorbis voices decoded if the `ogg` feature is enabled
+//! - Shift-JIS name & comment text handling
 //!
 //! ## License
 //!
@@ -21,7 +20,11 @@ mod value;
 pub use self::value::*;
 
 pub mod data;
+pub mod project;
+pub mod ptnoise;
 pub mod ptvoice;
+pub mod render;
+pub mod text;
 pub mod voice;
 
 //--------------------------------------------------------------------------------------------------
@@ -29,6 +32,10 @@ pub mod voice;
 /// Re-exports for common use cases.
 pub mod prelude {
     pub use super::data::{FromRead, WriteTo};
+    pub use super::project::{Project, ProjectError, ProjectFormat};
+    pub use super::ptnoise::{PtnError, PtnOscillator, PtnUnit, PtnWaveType, Ptnoise};
     pub use super::ptvoice::{PtvEnvelope, PtvError, PtvUnit, PtvWave, Ptvoice};
+    pub use super::render::{Renderer, Sample};
+    pub use super::text::{Text, TextError};
     pub use super::{Key, PanVolume, Tuning, Volume};
 }
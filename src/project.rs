@@ -0,0 +1,577 @@
+//! pxtone project (ptcop/pttune) functionality.
+
+mod assist;
+mod block;
+mod edit;
+mod effect;
+mod error;
+mod event;
+mod master;
+mod material;
+mod note;
+mod timing;
+mod unknown;
+pub use self::assist::*;
+pub use self::effect::*;
+pub use self::error::*;
+pub use self::event::*;
+pub use self::master::*;
+pub use self::note::*;
+pub use self::timing::*;
+pub use self::unknown::*;
+
+use self::block::{BlockSink, BlockTag};
+use crate::data::{FromRead, WriteTo};
+use crate::text::{Text, TextError};
+
+use std::io::{Read, Seek, Write};
+use std::ops::Range;
+
+//--------------------------------------------------------------------------------------------------
+
+type ProjectSignature = [u8; 16];
+
+/// Kind of project file, which determines the header written before project data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProjectFormat {
+    /// Editable project (`.ptcop`) as saved by pxtone Collage.
+    Collage,
+    /// Playback-only project (`.pttune`) as exported by pxtone Collage. Like pxtone, the project
+    /// name & comment and unit & voice names aren't written in this format.
+    ///
+    /// ```
+    /// # use ptcrab::data::{FromRead, WriteTo};
+    /// # use ptcrab::project::{Event, EventKind, Project, ProjectError, ProjectFormat, Unit};
+    /// # use std::io::Cursor;
+    /// let mut project = Project::new(ProjectFormat::Tune);
+    /// project.name = Some(Box::new(*b"song"));
+    /// project.units = Box::new([Unit::new(Some(*b"bass\0\0\0\0\0\0\0\0\0\0\0\0"))]);
+    /// project.events = Box::new([Event::new(0, 0, EventKind::On(480))]);
+    /// let mut data = Cursor::new(vec![]);
+    /// project.write_to(&mut data)?;
+    ///
+    /// data.set_position(0);
+    /// let read = Project::from_read(&mut data)?;
+    /// project.name = None;
+    /// project.units[0].name = None;
+    /// assert_eq!(read, project);
+    /// # Ok::<(), ProjectError>(())
+    /// ```
+    Tune,
+}
+
+impl ProjectFormat {
+    /// Returns the string present at the start of project data in this format.
+    const fn signature(self) -> ProjectSignature {
+        match self {
+            Self::Collage => *b"PTCOLLAGE-071119",
+            Self::Tune => *b"PTTUNE--20071119",
+        }
+    }
+}
+
+/// Song made up of units playing voices, as arranged in pxtone Collage.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Project {
+    /// File format of the project.
+    pub format: ProjectFormat,
+    /// Version of the pxtone editor that last saved the project.
+    pub editor_version: u16,
+
+    /// Project-wide timing settings.
+    pub master: Master,
+    /// Every event in the project. Events don't need to be kept in order, since they're sorted
+    /// when written (see [`Event::cmp_order`]).
+    pub events: Box<[Event]>,
+    /// Shift-JIS encoded project name, if any. See [`Self::name_text`]. Not written in the
+    /// [`ProjectFormat::Tune`] format.
+    pub name: Option<Box<[u8]>>,
+    /// Shift-JIS encoded project comment, if any. See [`Self::comment_text`]. Not written in the
+    /// [`ProjectFormat::Tune`] format.
+    pub comment: Option<Box<[u8]>>,
+
+    /// Delay effects. Projects with more than [`Self::MAX_DELAYS`] delays can't be read or
+    /// written, since pxtone refuses to load them.
+    pub delays: Box<[Delay]>,
+    /// Overdrive effects. Projects with more than [`Self::MAX_OVERDRIVES`] overdrives can't be
+    /// read or written, since pxtone refuses to load them.
+    pub overdrives: Box<[Overdrive]>,
+
+    /// Voices available to units, referred to by index. Events that switch to nonexistent voices
+    /// can't be written, nor can projects with more than [`Self::MAX_VOICES`] voices, since pxtone
+    /// refuses to load them.
+    pub voices: Box<[ProjectVoice]>,
+    /// Units that play events, referred to by index. Events for nonexistent units can't be
+    /// written, nor can projects with more than [`Self::MAX_UNITS`] units, since pxtone refuses to
+    /// load them.
+    pub units: Box<[Unit]>,
+
+    /// Blocks that ptcrab doesn't understand, which are preserved so that no data is lost when the
+    /// project is written back out.
+    pub unknown_blocks: Box<[UnknownBlock]>,
+}
+
+impl Project {
+    /// Number of unit groups, which are mixed separately & have effects applied to them.
+    pub const GROUP_COUNT: usize = 7;
+    /// Maximum number of delays that pxtone will load.
+    pub const MAX_DELAYS: usize = 4;
+    /// Maximum number of overdrives that pxtone will load.
+    pub const MAX_OVERDRIVES: usize = 2;
+    /// Maximum number of voices that pxtone will load.
+    pub const MAX_VOICES: usize = 100;
+    /// Maximum number of units that pxtone will load.
+    pub const MAX_UNITS: usize = 50;
+
+    /// Creates an empty project in the given format.
+    pub fn new(format: ProjectFormat) -> Self {
+        Self {
+            format,
+            editor_version: 0,
+
+            master: Master::default(),
+            events: Box::new([]),
+            name: None,
+            comment: None,
+
+            delays: Box::new([]),
+            overdrives: Box::new([]),
+
+            voices: Box::new([]),
+            units: Box::new([]),
+
+            unknown_blocks: Box::new([]),
+        }
+    }
+
+    /// Returns the decoded project name, if any.
+    pub fn name_text(&self) -> Option<Text> {
+        self.name.as_deref().map(Text::decode)
+    }
+    /// Sets the project name from text.
+    pub fn set_name_text(&mut self, name: Option<Text>) -> Result<(), TextError> {
+        self.name = name.map(|name| name.encode()).transpose()?;
+        Ok(())
+    }
+    /// Returns the decoded project comment, if any.
+    pub fn comment_text(&self) -> Option<Text> {
+        self.comment.as_deref().map(Text::decode)
+    }
+    /// Sets the project comment from text.
+    ///
+    /// ```
+    /// # use ptcrab::project::Project;
+    /// # use ptcrab::text::TextError;
+    /// let mut project = Project::default();
+    /// project.set_comment_text(Some("作曲: ぴすとん".into()))?;
+    /// assert_eq!(project.comment_text().unwrap().to_string(), "作曲: ぴすとん");
+    ///
+    /// let result = project.set_comment_text(Some("🎵".into()));
+    /// assert_eq!(result, Err(TextError::Unencodable('🎵')));
+    /// # Ok::<(), TextError>(())
+    /// ```
+    pub fn set_comment_text(&mut self, comment: Option<Text>) -> Result<(), TextError> {
+        self.comment = comment.map(|comment| comment.encode()).transpose()?;
+        Ok(())
+    }
+
+    /// Sorts events the way pxtone does, keeping the relative order of events with the same clock
+    /// & kind.
+    pub fn sort_events(&mut self) {
+        self.events.sort_by(Event::cmp_order);
+    }
+
+    /// Returns the notes played by the given unit. See [`Note::from_events`].
+    pub fn notes(&self, unit: u8) -> Box<[Note]> {
+        Note::from_events(&self.events, unit)
+    }
+    /// Replaces the notes played by the given unit, removing its existing note, key, velocity &
+    /// voice events and adding the events from [`Note::to_events`]. Since only the key at the start
+    /// of each note is kept, key changes during notes are lost.
+    ///
+    /// Fails with [`ProjectError::Invalid`] if any note plays a nonexistent voice, leaving the
+    /// unit's notes unchanged.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Note, Project, ProjectError, Unit};
+    /// # use ptcrab::ptvoice::Ptvoice;
+    /// # use ptcrab::Key;
+    /// let mut project = Project::default();
+    /// project.units = vec![Unit::default(); 2].into();
+    /// project.voices = vec![Ptvoice::new(Box::new([])).into(); 2].into();
+    /// project.events = Box::new([
+    ///     Event::new(0, 0, EventKind::Key(Key::C4)),
+    ///     Event::new(0, 0, EventKind::On(960)),
+    ///     Event::new(0, 0, EventKind::Volume(64.into())),
+    ///     Event::new(0, 1, EventKind::On(480)),
+    /// ]);
+    ///
+    /// let mut notes = project.notes(0);
+    /// notes[0].length = 480;
+    /// notes[0].voice = 1;
+    /// project.set_notes(0, &notes)?;
+    ///
+    /// assert_eq!(project.notes(0), notes);
+    /// assert_eq!(project.notes(1), Box::new([Note::new(0, 480, Key::A4)]) as Box<[_]>);
+    /// // Other events are kept as-is.
+    /// assert!(project.events.contains(&Event::new(0, 0, EventKind::Volume(64.into()))));
+    ///
+    /// notes[0].voice = 2;
+    /// assert!(matches!(project.set_notes(0, &notes), Err(ProjectError::Invalid)));
+    /// # Ok::<(), ProjectError>(())
+    /// ```
+    pub fn set_notes(&mut self, unit: u8, notes: &[Note]) -> Result<(), ProjectError> {
+        if notes.iter().any(|note| note.voice >= self.voices.len()) {
+            return Err(ProjectError::Invalid);
+        }
+        let mut events = std::mem::take(&mut self.events).into_vec();
+        events.retain(|event| {
+            (event.unit != unit)
+                || !matches!(
+                    event.kind,
+                    EventKind::On(_)
+                        | EventKind::Key(_)
+                        | EventKind::Velocity(_)
+                        | EventKind::Voice(_)
+                )
+        });
+        events.extend(Note::to_events(notes, unit).into_vec());
+        self.events = events.into_boxed_slice();
+
+        Ok(())
+    }
+
+    /// Returns whether a voice event's value refers to an existing voice.
+    fn has_voice(&self, voice: i32) -> bool {
+        usize::try_from(voice).is_ok_and(|voice| voice < self.voices.len())
+    }
+
+    /// Returns the number of whole measures needed to fit every event, including the full length
+    /// of notes & portamento. This is at least 1.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Project};
+    /// let mut project = Project::default();
+    /// project.events = Box::new([Event::new(1920, 0, EventKind::On(1))]);
+    /// assert_eq!(project.measure_count(), 2);
+    ///
+    /// // Clocks near the limit don't overflow.
+    /// project.events = Box::new([Event::new(i32::MAX, 0, EventKind::On(i32::MAX))]);
+    /// assert!(project.measure_count() >= i32::MAX / 1920);
+    /// ```
+    pub fn measure_count(&self) -> i32 {
+        let last_clock = self
+            .events
+            .iter()
+            .map(|event| match event.kind {
+                EventKind::On(len) | EventKind::Portamento(len) => event.clock.saturating_add(len),
+                _ => event.clock,
+            })
+            .max()
+            .unwrap_or(0);
+
+        let beat_clock = (self.master.beat_clock as i32).max(1);
+        let beats_per_measure = (self.master.beats_per_measure as i32).max(1);
+        let beat_count = last_clock.saturating_add(beat_clock - 1) / beat_clock;
+        (beat_count.saturating_add(beats_per_measure - 1) / beats_per_measure).max(1)
+    }
+
+    /// Returns the range of clocks that pxtone loops over, from the repeat measure up to either the
+    /// last measure or the end of the last measure with events.
+    ///
+    /// Like pxtone, loop points beyond the end of the project are ignored.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Project};
+    /// let mut project = Project::default();
+    /// project.events = Box::new([Event::new(0, 0, EventKind::On(1920 * 3 + 1))]);
+    /// assert_eq!(project.loop_range(), 0..(1920 * 4));
+    ///
+    /// project.master.set_repeat_measure(1);
+    /// project.master.set_last_measure(Some(2));
+    /// assert_eq!(project.loop_range(), 1920..(1920 * 2));
+    /// ```
+    pub fn loop_range(&self) -> Range<i32> {
+        let measure_count = self.measure_count();
+        let repeat_measure = Some(self.master.repeat_measure())
+            .filter(|&measure| measure < measure_count)
+            .unwrap_or(0);
+        let last_measure = self
+            .master
+            .last_measure()
+            .filter(|&measure| measure > 0)
+            .map_or(measure_count, |measure| measure.min(measure_count));
+
+        let measure_clock = self.master.measure_clock();
+        (repeat_measure * measure_clock)..last_measure.saturating_mul(measure_clock)
+    }
+}
+
+impl FromRead<Self> for Project {
+    type Error = ProjectError;
+
+    fn from_read<R: Read>(source: &mut R) -> Result<Self, Self::Error> {
+        // Check signature at start of data, which also specifies format version.
+        let signature = ProjectSignature::from_read(source)?;
+        let format = [ProjectFormat::Collage, ProjectFormat::Tune]
+            .into_iter()
+            .find(|format| format.signature() == signature);
+        let Some(format) = format else {
+            return Err(
+                if signature.starts_with(b"PTCOLLAGE-") || signature.starts_with(b"PTTUNE--") {
+                    ProjectError::Unsupported
+                } else {
+                    ProjectError::Invalid
+                },
+            );
+        };
+        let editor_version = u16::from_read(source)?;
+        // Reserved value.
+        let _ = u16::from_read(source)?;
+
+        let mut project = Self {
+            editor_version,
+            ..Self::new(format)
+        };
+        let mut events = vec![];
+        let mut delays = vec![];
+        let mut overdrives = vec![];
+        let mut voices = vec![];
+        let mut voice_names = vec![];
+        let mut unit_count = 0;
+        let mut unit_names = vec![];
+        let mut unknown_blocks = vec![];
+        let mut known_count = 0;
+
+        // Read blocks until the end marker.
+        loop {
+            let tag = BlockTag::from_read(source)?;
+            match tag {
+                block::END => break,
+                // The event list's size is unreliable, so it has to be read in place.
+                block::EVENTS => events.extend(event::read_list(source)?.into_vec()),
+                _ => {
+                    let data = block::read_data(source)?;
+                    let expect_size = |size| match data.len() == size {
+                        true => Ok(&data[..]),
+                        false => Err(ProjectError::Invalid),
+                    };
+
+                    match tag {
+                        block::MASTER => {
+                            project.master = Master::from_read(&mut expect_size(Master::SIZE)?)?
+                        }
+                        block::NAME => project.name = Some(data.clone()),
+                        block::COMMENT => project.comment = Some(data.clone()),
+                        block::DELAY => {
+                            delays.push(Delay::from_read(&mut expect_size(Delay::SIZE)?)?)
+                        }
+                        block::OVERDRIVE => overdrives
+                            .push(Overdrive::from_read(&mut expect_size(Overdrive::SIZE)?)?),
+                        block::PCM | block::PTV | block::PTN | block::OGG => {
+                            voices.push(material::read(&tag, &data)?)
+                        }
+                        block::VOICE_NAME => voice_names.push(NameAssignment::from_read(
+                            &mut expect_size(NameAssignment::SIZE)?,
+                        )?),
+                        block::UNIT_COUNT => {
+                            let source = &mut expect_size(4)?;
+                            unit_count = u16::from_read(source)?;
+                            if u16::from_read(source)? != 0 {
+                                return Err(ProjectError::Invalid);
+                            }
+                        }
+                        block::UNIT_NAME => unit_names.push(NameAssignment::from_read(
+                            &mut expect_size(NameAssignment::SIZE)?,
+                        )?),
+                        _ => {
+                            unknown_blocks.push(UnknownBlock {
+                                tag,
+                                position: known_count,
+                                data,
+                            });
+                            continue;
+                        }
+                    }
+                }
+            }
+            known_count += 1;
+        }
+
+        // Like pxtone, projects with too many effects are refused.
+        if (delays.len() > Self::MAX_DELAYS) || (overdrives.len() > Self::MAX_OVERDRIVES) {
+            return Err(ProjectError::Oversized);
+        }
+        project.events = events.into_boxed_slice();
+        project.delays = delays.into_boxed_slice();
+        project.overdrives = overdrives.into_boxed_slice();
+        project.voices = voices.into_iter().map(ProjectVoice::from).collect();
+        project.units = vec![Unit::default(); unit_count as usize].into_boxed_slice();
+        // Like pxtone, names for nonexistent voices or units are invalid.
+        for NameAssignment { index, name } in voice_names {
+            let voice = project.voices.get_mut(index as usize);
+            voice.ok_or(ProjectError::Invalid)?.name = Some(name);
+        }
+        for NameAssignment { index, name } in unit_names {
+            let unit = project.units.get_mut(index as usize);
+            unit.ok_or(ProjectError::Invalid)?.name = Some(name);
+        }
+        project.unknown_blocks = unknown_blocks.into_boxed_slice();
+
+        Ok(project)
+    }
+}
+
+impl WriteTo for Project {
+    type Error = ProjectError;
+
+    /// Writes the project, ordering blocks the same way pxtone does. As with pxtone, names &
+    /// comments are left out of [`ProjectFormat::Tune`] projects. Unknown blocks are placed back
+    /// among known blocks where they were read from, so projects saved by pxtone are reproduced
+    /// byte-for-byte if left unmodified.
+    ///
+    /// Fails with [`ProjectError::Invalid`] if any event is for a nonexistent unit or switches to a
+    /// nonexistent voice, or if any overdrive's cut or amp is out of range. Fails with
+    /// [`ProjectError::Oversized`] if there are more units, voices or effects than pxtone will
+    /// load.
+    ///
+    /// ```
+    /// # use ptcrab::data::{FromRead, WriteTo};
+    /// # use ptcrab::project::{Delay, DelayUnit, Event, EventKind, Overdrive, Project, ProjectError};
+    /// # use ptcrab::project::Unit;
+    /// # use std::io::Cursor;
+    /// let mut project = Project::default();
+    /// project.events = Box::new([Event::new(0, 1, EventKind::On(480))]);
+    /// project.units = Box::new([Unit::new(Some(*b"drums\0\0\0\0\0\0\0\0\0\0\0"))]);
+    /// let mut data = Cursor::new(vec![]);
+    /// assert!(matches!(project.write_to(&mut data), Err(ProjectError::Invalid)));
+    ///
+    /// project.units = Box::new([Unit::default(), Unit::default()]);
+    /// project.units[1].name = Some(*b"bass\0\0\0\0\0\0\0\0\0\0\0\0");
+    /// // Invalid delay groups are kept as-is, even though pxtone plays them on group 0.
+    /// project.delays = Box::new([Delay::new(DelayUnit::Beat, 9, 33., 1.)]);
+    /// project.write_to(&mut data)?;
+    /// data.set_position(0);
+    /// assert_eq!(Project::from_read(&mut data)?, project);
+    ///
+    /// project.delays = vec![project.delays[0].clone(); Project::MAX_DELAYS + 1].into();
+    /// let result = project.write_to(&mut Cursor::new(vec![]));
+    /// assert!(matches!(result, Err(ProjectError::Oversized)));
+    ///
+    /// project.delays = Box::new([]);
+    /// project.units = vec![Unit::default(); Project::MAX_UNITS + 1].into();
+    /// let result = project.write_to(&mut Cursor::new(vec![]));
+    /// assert!(matches!(result, Err(ProjectError::Oversized)));
+    ///
+    /// // The project has no voices to switch to.
+    /// project.events = Box::new([Event::new(0, 1, EventKind::Voice(0))]);
+    /// let result = project.write_to(&mut Cursor::new(vec![]));
+    /// assert!(matches!(result, Err(ProjectError::Invalid)));
+    ///
+    /// // pxtone only loads overdrives that cut at least 50%.
+    /// project.events = Box::new([]);
+    /// project.units = Box::new([]);
+    /// project.overdrives = Box::new([Overdrive::new(0, 10., 2.)]);
+    /// let result = project.write_to(&mut Cursor::new(vec![]));
+    /// assert!(matches!(result, Err(ProjectError::Invalid)));
+    /// # Ok::<(), ProjectError>(())
+    /// ```
+    fn write_to<W: Write + Seek>(&self, sink: &mut W) -> Result<u64, Self::Error> {
+        // Make sure every event refers to an existing unit & voice.
+        if self.events.iter().any(|event| {
+            (event.unit as usize >= self.units.len())
+                || matches!(event.kind, EventKind::Voice(voice) if !self.has_voice(voice))
+        }) {
+            return Err(ProjectError::Invalid);
+        }
+        if !self.overdrives.iter().all(Overdrive::is_in_range) {
+            return Err(ProjectError::Invalid);
+        }
+        if (self.units.len() > Self::MAX_UNITS)
+            || (self.voices.len() > Self::MAX_VOICES)
+            || (self.delays.len() > Self::MAX_DELAYS)
+            || (self.overdrives.len() > Self::MAX_OVERDRIVES)
+        {
+            return Err(ProjectError::Oversized);
+        }
+        let unit_count = self.units.len() as u16;
+
+        // Project signature, editor version & reserved value.
+        let start_pos = self.format.signature().write_to(sink)?;
+        self.editor_version.write_to(sink)?;
+        0_u16.write_to(sink)?;
+
+        // Unknown blocks are interleaved with known blocks as they're written.
+        let mut blocks = BlockSink::new(sink, &self.unknown_blocks);
+
+        blocks.write(&block::MASTER, |sink| {
+            self.master.write_to(sink)?;
+            Ok(())
+        })?;
+        // The event list's size is written separately to replicate pxtone.
+        let sink = blocks.next_known()?;
+        block::EVENTS.write_to(sink)?;
+        event::write_list(sink, &self.events)?;
+
+        // Like pxtone, only Collage projects include text & assist blocks.
+        let is_collage = self.format == ProjectFormat::Collage;
+
+        for (tag, text) in [(block::NAME, &self.name), (block::COMMENT, &self.comment)] {
+            if let Some(text) = text.as_ref().filter(|_| is_collage) {
+                blocks.write(&tag, |sink| Ok(sink.write_all(text)?))?;
+            }
+        }
+
+        for delay in self.delays.iter() {
+            blocks.write(&block::DELAY, |sink| {
+                delay.write_to(sink)?;
+                Ok(())
+            })?;
+        }
+        for overdrive in self.overdrives.iter() {
+            blocks.write(&block::OVERDRIVE, |sink| {
+                overdrive.write_to(sink)?;
+                Ok(())
+            })?;
+        }
+
+        // Each voice is followed by its name, if any.
+        let write_name = |blocks: &mut BlockSink<W>, tag: &BlockTag, index: usize, name| {
+            let name = NameAssignment {
+                index: index as u16,
+                name,
+            };
+            blocks.write(tag, |sink| {
+                name.write_to(sink)?;
+                Ok(())
+            })
+        };
+        for (index, voice) in self.voices.iter().enumerate() {
+            material::write(blocks.next_known()?, &voice.voice)?;
+            if let Some(name) = voice.name.filter(|_| is_collage) {
+                write_name(&mut blocks, &block::VOICE_NAME, index, name)?;
+            }
+        }
+
+        blocks.write(&block::UNIT_COUNT, |sink| {
+            unit_count.write_to(sink)?;
+            0_u16.write_to(sink)?;
+            Ok(())
+        })?;
+        for (index, unit) in self.units.iter().enumerate() {
+            if let Some(name) = unit.name.filter(|_| is_collage) {
+                write_name(&mut blocks, &block::UNIT_NAME, index, name)?;
+            }
+        }
+
+        blocks.finish()?;
+
+        Ok(start_pos)
+    }
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self::new(ProjectFormat::Collage)
+    }
+}
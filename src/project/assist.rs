@@ -0,0 +1,134 @@
+use super::ProjectError;
+use crate::data::{FromRead, WriteTo};
+use crate::ptvoice::Ptvoice;
+use crate::text::{Text, TextError};
+use crate::voice::{OggVoice, PcmVoice, PtnVoice, Voice};
+
+use duplicate::duplicate_item;
+use std::io::{Read, Seek, Write};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Shift-JIS encoded name of a unit or voice, padded with null bytes.
+pub type RawName = [u8; 16];
+
+/// Track of a project, which plays events using voices.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Unit {
+    /// Name shown in pxtone Collage, if any. Not written in the
+    /// [`ProjectFormat::Tune`](super::ProjectFormat::Tune) format.
+    pub name: Option<RawName>,
+}
+
+impl Unit {
+    /// Creates a unit with the given name.
+    pub fn new(name: Option<RawName>) -> Self {
+        Self { name }
+    }
+
+    /// Returns the decoded name, if any.
+    pub fn name_text(&self) -> Option<Text> {
+        self.name.as_ref().map(|name| Text::decode_fixed(name))
+    }
+    /// Sets the name from text, truncating it to fit (see [`Text::encode_fixed`]).
+    ///
+    /// ```
+    /// # use ptcrab::project::Unit;
+    /// let mut unit = Unit::default();
+    /// unit.set_name_text(Some("ベース".into()))?;
+    /// assert_eq!(unit.name_text().unwrap().to_string(), "ベース");
+    /// # Ok::<(), ptcrab::text::TextError>(())
+    /// ```
+    pub fn set_name_text(&mut self, name: Option<Text>) -> Result<(), TextError> {
+        self.name = name.map(|name| name.encode_fixed()).transpose()?;
+        Ok(())
+    }
+}
+
+/// Voice available to a project's units, along with its name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectVoice {
+    /// Name shown in pxtone Collage, if any. Not written in the
+    /// [`ProjectFormat::Tune`](super::ProjectFormat::Tune) format.
+    pub name: Option<RawName>,
+    /// Voice data.
+    pub voice: Voice,
+}
+
+impl ProjectVoice {
+    /// Creates a project voice with the given name.
+    pub fn new(name: Option<RawName>, voice: Voice) -> Self {
+        Self { name, voice }
+    }
+
+    /// Returns the decoded name, if any.
+    pub fn name_text(&self) -> Option<Text> {
+        self.name.as_ref().map(|name| Text::decode_fixed(name))
+    }
+    /// Sets the name from text, truncating it to fit (see [`Text::encode_fixed`]).
+    pub fn set_name_text(&mut self, name: Option<Text>) -> Result<(), TextError> {
+        self.name = name.map(|name| name.encode_fixed()).transpose()?;
+        Ok(())
+    }
+}
+
+impl From<Voice> for ProjectVoice {
+    /// Creates an unnamed project voice.
+    fn from(value: Voice) -> Self {
+        Self::new(None, value)
+    }
+}
+#[duplicate_item(
+    _Voice_;
+    [Ptvoice];
+    [PtnVoice];
+    [PcmVoice];
+    [OggVoice];
+)]
+impl From<_Voice_> for ProjectVoice {
+    /// Creates an unnamed project voice.
+    fn from(value: _Voice_) -> Self {
+        Self::new(None, value.into())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Name given to a voice or unit, identified by its index.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct NameAssignment {
+    /// Index of the named voice or unit.
+    pub index: u16,
+    pub name: RawName,
+}
+
+impl NameAssignment {
+    /// Size of name assignment block data.
+    pub const SIZE: usize = 20;
+}
+
+impl FromRead<Self> for NameAssignment {
+    type Error = ProjectError;
+
+    fn from_read<R: Read>(source: &mut R) -> Result<Self, Self::Error> {
+        let index = u16::from_read(source)?;
+        if u16::from_read(source)? != 0 {
+            return Err(ProjectError::Invalid);
+        }
+        let name = RawName::from_read(source)?;
+
+        Ok(Self { index, name })
+    }
+}
+
+impl WriteTo for NameAssignment {
+    type Error = ProjectError;
+
+    fn write_to<W: Write + Seek>(&self, sink: &mut W) -> Result<u64, Self::Error> {
+        let start_pos = self.index.write_to(sink)?;
+        0_u16.write_to(sink)?;
+        self.name.write_to(sink)?;
+
+        Ok(start_pos)
+    }
+}
@@ -0,0 +1,127 @@
+use super::{ProjectError, UnknownBlock};
+use crate::data::{FromRead, WriteTo};
+
+use std::io::{Error as IoError, ErrorKind, Read, Seek, SeekFrom, Write};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Tag identifying the contents of a project block.
+pub(crate) type BlockTag = [u8; 8];
+
+pub(crate) const MASTER: BlockTag = *b"MasterV5";
+pub(crate) const EVENTS: BlockTag = *b"Event V5";
+pub(crate) const NAME: BlockTag = *b"textNAME";
+pub(crate) const COMMENT: BlockTag = *b"textCOMM";
+pub(crate) const DELAY: BlockTag = *b"effeDELA";
+pub(crate) const OVERDRIVE: BlockTag = *b"effeOVER";
+pub(crate) const PCM: BlockTag = *b"matePCM ";
+pub(crate) const PTV: BlockTag = *b"matePTV ";
+pub(crate) const PTN: BlockTag = *b"matePTN ";
+pub(crate) const OGG: BlockTag = *b"mateOGGV";
+pub(crate) const VOICE_NAME: BlockTag = *b"assiWOIC";
+pub(crate) const UNIT_COUNT: BlockTag = *b"num UNIT";
+pub(crate) const UNIT_NAME: BlockTag = *b"assiUNIT";
+pub(crate) const END: BlockTag = *b"pxtoneND";
+
+/// Reads a block's size, then returns that many bytes of block data.
+pub(crate) fn read_data<R: Read>(source: &mut R) -> Result<Box<[u8]>, ProjectError> {
+    let size: u64 = i32::from_read(source)?
+        .try_into()
+        .map_err(|_| ProjectError::Invalid)?;
+
+    // Read incrementally rather than trusting the size with a single allocation.
+    let mut data = Vec::new();
+    if source.take(size).read_to_end(&mut data)? as u64 != size {
+        return Err(IoError::from(ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(data.into_boxed_slice())
+}
+
+/// Writes a block with the given tag, where `write_data` writes the block data after its size.
+pub(crate) fn write<W, F>(sink: &mut W, tag: &BlockTag, write_data: F) -> Result<u64, ProjectError>
+where
+    W: Write + Seek,
+    F: FnOnce(&mut W) -> Result<(), ProjectError>,
+{
+    let start_pos = tag.write_to(sink)?;
+    // Placeholder for data size (to be written later).
+    let size_pos = 0_i32.write_to(sink)?;
+
+    let data_start = sink.stream_position()?;
+    write_data(sink)?;
+
+    // Go back to update data size.
+    let data_end = sink.stream_position()?;
+    let size = i32::try_from(data_end - data_start).map_err(|_| ProjectError::Oversized)?;
+    sink.seek(SeekFrom::Start(size_pos))?;
+    size.write_to(sink)?;
+    sink.seek(SeekFrom::Start(data_end))?;
+
+    Ok(start_pos)
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Sink for project blocks that places unknown blocks back where they were originally read.
+pub(crate) struct BlockSink<'a, W> {
+    sink: &'a mut W,
+    /// Unknown blocks ordered by position, in their original order otherwise.
+    unknown: Vec<&'a UnknownBlock>,
+    /// Number of unknown blocks written so far.
+    unknown_count: usize,
+    /// Number of known blocks written so far.
+    known_count: usize,
+}
+
+impl<'a, W: Write + Seek> BlockSink<'a, W> {
+    pub fn new(sink: &'a mut W, unknown: &'a [UnknownBlock]) -> Self {
+        let mut unknown: Vec<_> = unknown.iter().collect();
+        unknown.sort_by_key(|block| block.position);
+
+        Self {
+            sink,
+            unknown,
+            unknown_count: 0,
+            known_count: 0,
+        }
+    }
+
+    /// Writes any unknown blocks due before the next known block, then returns the underlying sink
+    /// for the known block to be written to.
+    pub fn next_known(&mut self) -> Result<&mut W, ProjectError> {
+        self.write_unknown(self.known_count)?;
+        self.known_count += 1;
+        Ok(self.sink)
+    }
+
+    /// Writes a known block with the given tag, where `write_data` writes the block data after its
+    /// size.
+    pub fn write<F>(&mut self, tag: &BlockTag, write_data: F) -> Result<u64, ProjectError>
+    where
+        F: FnOnce(&mut W) -> Result<(), ProjectError>,
+    {
+        write(self.next_known()?, tag, write_data)
+    }
+
+    /// Writes all remaining unknown blocks, followed by the end marker.
+    pub fn finish(mut self) -> Result<u64, ProjectError> {
+        self.write_unknown(usize::MAX)?;
+        // The end marker has a size of 0.
+        write(self.sink, &END, |_| Ok(()))
+    }
+
+    fn write_unknown(&mut self, max_position: usize) -> Result<(), ProjectError> {
+        while let Some(block) = self
+            .unknown
+            .get(self.unknown_count)
+            .filter(|block| block.position <= max_position)
+        {
+            write(self.sink, &block.tag, |sink| {
+                Ok(sink.write_all(&block.data)?)
+            })?;
+            self.unknown_count += 1;
+        }
+        Ok(())
+    }
+}
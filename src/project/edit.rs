@@ -0,0 +1,386 @@
+use super::{Event, EventKind, Note, Project, ProjectError, ProjectVoice, Unit};
+
+use std::mem::discriminant;
+
+//--------------------------------------------------------------------------------------------------
+
+impl Project {
+    /// Adds a unit after every existing unit, returning its index.
+    ///
+    /// Fails with [`ProjectError::Oversized`] if the project already has [`Self::MAX_UNITS`]
+    /// units.
+    pub fn add_unit(&mut self, unit: Unit) -> Result<u8, ProjectError> {
+        if self.units.len() >= Self::MAX_UNITS {
+            return Err(ProjectError::Oversized);
+        }
+        let mut units = std::mem::take(&mut self.units).into_vec();
+        units.push(unit);
+        self.units = units.into_boxed_slice();
+
+        Ok((self.units.len() - 1) as u8)
+    }
+    /// Removes the unit at the given index along with all of its events, shifting later units
+    /// (and their events) down to fill the gap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no unit at the given index.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Project, Unit};
+    /// let mut project = Project::default();
+    /// project.units = vec![Unit::default(); 3].into();
+    /// project.events = Box::new([
+    ///     Event::new(0, 0, EventKind::On(480)),
+    ///     Event::new(0, 1, EventKind::On(480)),
+    ///     Event::new(0, 2, EventKind::On(960)),
+    /// ]);
+    ///
+    /// project.remove_unit(1);
+    /// assert_eq!(project.units.len(), 2);
+    /// assert_eq!(
+    ///     *project.events,
+    ///     [Event::new(0, 0, EventKind::On(480)), Event::new(0, 1, EventKind::On(960))]
+    /// );
+    /// ```
+    pub fn remove_unit(&mut self, index: u8) -> Unit {
+        let mut units = std::mem::take(&mut self.units).into_vec();
+        let unit = units.remove(index as usize);
+        self.units = units.into_boxed_slice();
+
+        let mut events = std::mem::take(&mut self.events).into_vec();
+        events.retain(|event| event.unit != index);
+        for event in events.iter_mut().filter(|event| event.unit > index) {
+            event.unit -= 1;
+        }
+        self.events = events.into_boxed_slice();
+
+        unit
+    }
+    /// Moves the unit at index `from` to index `to`, shifting the units in between (and their
+    /// events) to make room.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no unit at either index.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Project, Unit};
+    /// let mut project = Project::default();
+    /// project.units = vec![Unit::default(); 3].into();
+    /// project.units[0].name = Some(*b"lead\0\0\0\0\0\0\0\0\0\0\0\0");
+    /// project.events = Box::new([
+    ///     Event::new(0, 0, EventKind::On(480)),
+    ///     Event::new(0, 1, EventKind::On(960)),
+    ///     Event::new(0, 2, EventKind::On(1440)),
+    /// ]);
+    ///
+    /// project.move_unit(0, 2);
+    /// assert_eq!(project.units[2].name, Some(*b"lead\0\0\0\0\0\0\0\0\0\0\0\0"));
+    /// assert_eq!(
+    ///     *project.events,
+    ///     [
+    ///         Event::new(0, 2, EventKind::On(480)),
+    ///         Event::new(0, 0, EventKind::On(960)),
+    ///         Event::new(0, 1, EventKind::On(1440)),
+    ///     ]
+    /// );
+    /// ```
+    pub fn move_unit(&mut self, from: u8, to: u8) {
+        move_item(&mut self.units, from as usize, to as usize);
+        for event in self.events.iter_mut() {
+            event.unit = moved_index(event.unit as usize, from as usize, to as usize) as u8;
+        }
+    }
+
+    /// Adds a voice after every existing voice, returning its index.
+    ///
+    /// Fails with [`ProjectError::Oversized`] if the project already has [`Self::MAX_VOICES`]
+    /// voices.
+    pub fn add_voice(&mut self, voice: impl Into<ProjectVoice>) -> Result<usize, ProjectError> {
+        if self.voices.len() >= Self::MAX_VOICES {
+            return Err(ProjectError::Oversized);
+        }
+        let mut voices = std::mem::take(&mut self.voices).into_vec();
+        voices.push(voice.into());
+        self.voices = voices.into_boxed_slice();
+
+        Ok(self.voices.len() - 1)
+    }
+    /// Removes the voice at the given index, shifting later voices down to fill the gap. Like
+    /// pxtone, events that switch to the removed voice are removed, and events that switch to later
+    /// voices are updated to match.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no voice at the given index.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Project};
+    /// # use ptcrab::ptvoice::Ptvoice;
+    /// let mut project = Project::default();
+    /// for _ in 0..3 {
+    ///     project.add_voice(Ptvoice::new(Box::new([])))?;
+    /// }
+    /// project.events = Box::new([
+    ///     Event::new(0, 0, EventKind::Voice(0)),
+    ///     Event::new(0, 1, EventKind::Voice(1)),
+    ///     Event::new(0, 2, EventKind::Voice(2)),
+    /// ]);
+    ///
+    /// project.remove_voice(1);
+    /// assert_eq!(project.voices.len(), 2);
+    /// assert_eq!(
+    ///     *project.events,
+    ///     [Event::new(0, 0, EventKind::Voice(0)), Event::new(0, 2, EventKind::Voice(1))]
+    /// );
+    /// # Ok::<(), ptcrab::project::ProjectError>(())
+    /// ```
+    pub fn remove_voice(&mut self, index: usize) -> ProjectVoice {
+        let mut voices = std::mem::take(&mut self.voices).into_vec();
+        let voice = voices.remove(index);
+        self.voices = voices.into_boxed_slice();
+
+        let mut events = std::mem::take(&mut self.events).into_vec();
+        events.retain(|event| event_voice(event) != Some(index));
+        for event in events.iter_mut() {
+            if let Some(voice) = event_voice(event).filter(|&voice| voice > index) {
+                event.kind = EventKind::Voice((voice - 1) as i32);
+            }
+        }
+        self.events = events.into_boxed_slice();
+
+        voice
+    }
+    /// Moves the voice at index `from` to index `to`, shifting the voices in between to make room.
+    /// Events that switch voices are updated to keep playing the same voices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no voice at either index.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Project};
+    /// # use ptcrab::ptvoice::Ptvoice;
+    /// let mut project = Project::default();
+    /// for _ in 0..3 {
+    ///     project.add_voice(Ptvoice::new(Box::new([])))?;
+    /// }
+    /// project.events = Box::new([
+    ///     Event::new(0, 0, EventKind::Voice(0)),
+    ///     Event::new(0, 1, EventKind::Voice(2)),
+    /// ]);
+    ///
+    /// project.move_voice(0, 2);
+    /// assert_eq!(project.events[0].kind, EventKind::Voice(2));
+    /// assert_eq!(project.events[1].kind, EventKind::Voice(1));
+    /// # Ok::<(), ptcrab::project::ProjectError>(())
+    /// ```
+    pub fn move_voice(&mut self, from: usize, to: usize) {
+        move_item(&mut self.voices, from, to);
+        for event in self.events.iter_mut() {
+            if let Some(voice) = event_voice(event) {
+                event.kind = EventKind::Voice(moved_index(voice, from, to) as i32);
+            }
+        }
+    }
+
+    /// Adds an event, such as a volume or panning change.
+    ///
+    /// Fails with [`ProjectError::Invalid`] if the event is for a nonexistent unit or switches to a
+    /// nonexistent voice.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Project, ProjectError, Unit};
+    /// let mut project = Project::default();
+    /// let unit = project.add_unit(Unit::default())?;
+    /// project.add_event(Event::new(0, unit, EventKind::Volume(64.into())))?;
+    /// assert_eq!(*project.events, [Event::new(0, unit, EventKind::Volume(64.into()))]);
+    ///
+    /// // There's no second unit, and no voices to switch to.
+    /// let result = project.add_event(Event::new(0, 1, EventKind::Volume(64.into())));
+    /// assert!(matches!(result, Err(ProjectError::Invalid)));
+    /// let result = project.add_event(Event::new(0, unit, EventKind::Voice(0)));
+    /// assert!(matches!(result, Err(ProjectError::Invalid)));
+    /// # Ok::<(), ProjectError>(())
+    /// ```
+    pub fn add_event(&mut self, event: Event) -> Result<(), ProjectError> {
+        if (event.unit as usize >= self.units.len())
+            || matches!(event.kind, EventKind::Voice(voice) if !self.has_voice(voice))
+        {
+            return Err(ProjectError::Invalid);
+        }
+        let mut events = std::mem::take(&mut self.events).into_vec();
+        events.push(event);
+        self.events = events.into_boxed_slice();
+
+        Ok(())
+    }
+    /// Adds notes to be played by the given unit, merging their events with the unit's existing
+    /// events. Key, velocity & voice events are added where a note differs from the unit's state
+    /// at its start clock, and the previous state is restored at the unit's next note so that
+    /// later notes play as before. Existing events, such as key changes during notes, are kept.
+    ///
+    /// Notes starting at the same clock share the same key, velocity & voice during playback, so
+    /// an existing note at an added note's start clock takes on the added note's values.
+    ///
+    /// Fails with [`ProjectError::Invalid`] if the unit doesn't exist or any note plays a
+    /// nonexistent voice.
+    ///
+    /// ```
+    /// # use ptcrab::data::WriteTo;
+    /// # use ptcrab::project::{Event, EventKind, Note, Position, Project, Unit};
+    /// # use ptcrab::ptvoice::{PtvUnit, Ptvoice};
+    /// # use ptcrab::Key;
+    /// # use std::io::Cursor;
+    /// let mut project = Project::default();
+    /// let voice = project.add_voice(Ptvoice::new(Box::new([PtvUnit::default()])))?;
+    /// let unit = project.add_unit(Unit::default())?;
+    ///
+    /// // Play a C major arpeggio over the second measure.
+    /// let timing = project.master.timing(44100);
+    /// let notes: Vec<_> = [0, 4, 7, 12]
+    ///     .into_iter()
+    ///     .enumerate()
+    ///     .map(|(beat, semitones)| Note {
+    ///         voice,
+    ///         ..Note::new(
+    ///             timing.clock_at(Position::new(1, beat as i32, 0)),
+    ///             timing.beat_clock(),
+    ///             Key::from_a4_offset((semitones - 9) * 256),
+    ///         )
+    ///     })
+    ///     .collect();
+    /// project.add_notes(unit, &notes)?;
+    ///
+    /// assert_eq!(*project.notes(unit), notes);
+    /// assert_eq!(project.measure_count(), 2);
+    /// project.write_to(&mut Cursor::new(vec![]))?;
+    ///
+    /// // Existing events are kept, such as this key change during the first note.
+    /// project.events = Box::new([
+    ///     Event::new(0, unit, EventKind::On(960)),
+    ///     Event::new(480, unit, EventKind::Key(Key::C4)),
+    ///     Event::new(1920, unit, EventKind::On(480)),
+    /// ]);
+    /// let quiet_note = Note { velocity: 64.into(), ..Note::new(960, 480, Key::C4) };
+    /// project.add_notes(unit, &[quiet_note.clone()])?;
+    /// assert!(project.events.contains(&Event::new(480, unit, EventKind::Key(Key::C4))));
+    /// assert_eq!(project.notes(unit)[1], quiet_note);
+    /// // The next note keeps its velocity.
+    /// assert_eq!(project.notes(unit)[2], Note::new(1920, 480, Key::C4));
+    /// # Ok::<(), ptcrab::project::ProjectError>(())
+    /// ```
+    pub fn add_notes(&mut self, unit: u8, notes: &[Note]) -> Result<(), ProjectError> {
+        if (unit as usize >= self.units.len())
+            || notes.iter().any(|note| note.voice >= self.voices.len())
+        {
+            return Err(ProjectError::Invalid);
+        }
+        let mut notes: Vec<_> = notes.iter().collect();
+        notes.sort_by_key(|note| note.start_clock);
+
+        let mut events = std::mem::take(&mut self.events).into_vec();
+        for note in notes {
+            let clock = note.start_clock;
+            let voice = i32::try_from(note.voice).unwrap_or(i32::MAX);
+            let states = [
+                EventKind::Voice(voice),
+                EventKind::Key(note.key),
+                EventKind::Velocity(note.velocity),
+            ];
+            let next_clock = events
+                .iter()
+                .filter(|event| (event.unit == unit) && (event.clock > clock))
+                .filter(|event| matches!(event.kind, EventKind::On(_)))
+                .map(|event| event.clock)
+                .min();
+
+            for (state, prev_state) in states.into_iter().zip(unit_state(&events, unit, clock)) {
+                if state == prev_state {
+                    continue;
+                }
+                let is_same_kind = |event: &Event| {
+                    (event.unit == unit) && (discriminant(&event.kind) == discriminant(&state))
+                };
+                // Restore the previous state for the next note, unless it's already changed.
+                let restore_clock = next_clock.filter(|&next_clock| {
+                    !events.iter().any(|event| {
+                        is_same_kind(event) && (event.clock > clock) && (event.clock <= next_clock)
+                    })
+                });
+                if let Some(restore_clock) = restore_clock {
+                    events.push(Event::new(restore_clock, unit, prev_state));
+                }
+                events.retain(|event| !(is_same_kind(event) && (event.clock == clock)));
+                events.push(Event::new(clock, unit, state));
+            }
+            events.push(Event::new(clock, unit, EventKind::On(note.length)));
+        }
+        self.events = events.into_boxed_slice();
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Returns the voice switched to by an event, if any.
+fn event_voice(event: &Event) -> Option<usize> {
+    match event.kind {
+        EventKind::Voice(voice) => usize::try_from(voice).ok(),
+        _ => None,
+    }
+}
+
+/// Returns the voice, key & velocity of a unit after every event up to & including the given clock,
+/// the same way as [`Note::from_events`].
+fn unit_state(events: &[Event], unit: u8, clock: i32) -> [EventKind; 3] {
+    let mut events: Vec<_> = events
+        .iter()
+        .filter(|event| (event.unit == unit) && (event.clock <= clock))
+        .collect();
+    events.sort_by(|a, b| a.cmp_order(b));
+
+    let mut state = [
+        EventKind::Voice(Note::DEFAULT_VOICE as i32),
+        EventKind::Key(Note::DEFAULT_KEY),
+        EventKind::Velocity(Note::DEFAULT_VELOCITY),
+    ];
+    for event in events {
+        match event.kind {
+            EventKind::Voice(voice) if voice >= 0 => state[0] = event.kind.clone(),
+            EventKind::Key(_) => state[1] = event.kind.clone(),
+            EventKind::Velocity(_) => state[2] = event.kind.clone(),
+            _ => {}
+        }
+    }
+
+    state
+}
+
+/// Moves an item from index `from` to index `to`, shifting the items in between.
+fn move_item<T>(items: &mut [T], from: usize, to: usize) {
+    assert!(
+        from < items.len() && to < items.len(),
+        "index out of bounds"
+    );
+    if from < to {
+        items[from..=to].rotate_left(1);
+    } else {
+        items[to..=from].rotate_right(1);
+    }
+}
+
+/// Returns the new index of an item after the item at index `from` is moved to index `to`.
+fn moved_index(index: usize, from: usize, to: usize) -> usize {
+    if index == from {
+        to
+    } else if (from < index) && (index <= to) {
+        index - 1
+    } else if (to <= index) && (index < from) {
+        index + 1
+    } else {
+        index
+    }
+}
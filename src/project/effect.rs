@@ -0,0 +1,203 @@
+use super::{Master, ProjectError};
+use crate::data::{FromRead, WriteTo};
+
+use std::io::{Read, Seek, Write};
+use std::ops::RangeInclusive;
+
+//--------------------------------------------------------------------------------------------------
+
+/// Unit of time that a delay's frequency is measured in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DelayUnit {
+    /// Repetitions per beat, which depends on the project's tempo.
+    Beat,
+    /// Repetitions per measure, which depends on the project's tempo & time signature.
+    Measure,
+    /// Repetitions per second.
+    Second,
+}
+
+impl DelayUnit {
+    /// Every delay unit, ordered by ID.
+    pub const ALL: [Self; 3] = [Self::Beat, Self::Measure, Self::Second];
+
+    /// Converts from pxtone's delay unit ID.
+    fn from_id(id: u16) -> Result<Self, ProjectError> {
+        Self::ALL
+            .get(id as usize)
+            .copied()
+            .ok_or(ProjectError::Invalid)
+    }
+}
+
+/// Delay (echo) effect applied to a unit group. The delayed signal is fed back into the delay, so
+/// each echo is quieter than the last.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Delay {
+    /// Unit of [`Self::frequency`].
+    pub unit: DelayUnit,
+    /// Index of the affected unit group. pxtone uses group 0 if this isn't a valid group.
+    pub group: u16,
+    /// Volume of the delayed signal as a percentage. Only the integer part is used by pxtone.
+    pub rate: f32,
+    /// Number of repetitions per [`Self::unit`]. The delay has no effect if this is 0.
+    pub frequency: f32,
+}
+
+impl Delay {
+    /// Size of delay block data.
+    pub(crate) const SIZE: usize = 12;
+
+    /// Creates a delay on the given group with the given volume (%) and frequency.
+    pub fn new(unit: DelayUnit, group: u16, rate: f32, frequency: f32) -> Self {
+        Self {
+            unit,
+            group,
+            rate,
+            frequency,
+        }
+    }
+
+    /// Returns the length of the delay in samples at the given sample rate, using the tempo & time
+    /// signature from `master`. This is 0 if the delay has no effect.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Delay, DelayUnit, Master};
+    /// let master = Master::new(480, 4, 120.);
+    /// let delay = Delay::new(DelayUnit::Beat, 0, 33., 2.);
+    /// assert_eq!(delay.sample_count(&master, 44100), 11025);
+    ///
+    /// let delay = Delay::new(DelayUnit::Measure, 0, 33., 2.);
+    /// assert_eq!(delay.sample_count(&master, 44100), 44100);
+    ///
+    /// let delay = Delay::new(DelayUnit::Second, 0, 33., 4.);
+    /// assert_eq!(delay.sample_count(&master, 48000), 12000);
+    /// ```
+    pub fn sample_count(&self, master: &Master, sample_rate: u32) -> usize {
+        if (self.frequency <= 0.) || ((self.rate as i32) == 0) {
+            return 0;
+        }
+        // Calculated in the same order as pxtone to get identical results.
+        let samples_per_minute = sample_rate as f32 * 60.;
+        let len = match self.unit {
+            DelayUnit::Beat => samples_per_minute / master.tempo / self.frequency,
+            DelayUnit::Measure => {
+                samples_per_minute * (master.beats_per_measure as f32)
+                    / master.tempo
+                    / self.frequency
+            }
+            DelayUnit::Second => (sample_rate as f32) / self.frequency,
+        };
+        len as usize
+    }
+}
+
+impl FromRead<Self> for Delay {
+    type Error = ProjectError;
+
+    fn from_read<R: Read>(source: &mut R) -> Result<Self, Self::Error> {
+        Ok(Self {
+            unit: DelayUnit::from_id(u16::from_read(source)?)?,
+            group: u16::from_read(source)?,
+            rate: f32::from_read(source)?,
+            frequency: f32::from_read(source)?,
+        })
+    }
+}
+
+impl WriteTo for Delay {
+    type Error = ProjectError;
+
+    fn write_to<W: Write + Seek>(&self, sink: &mut W) -> Result<u64, Self::Error> {
+        let start_pos = (self.unit as u16).write_to(sink)?;
+        self.group.write_to(sink)?;
+        self.rate.write_to(sink)?;
+        self.frequency.write_to(sink)?;
+
+        Ok(start_pos)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Overdrive (distortion) effect applied to a unit group, which clips the group's output and then
+/// amplifies it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Overdrive {
+    /// Index of the affected unit group. The overdrive has no effect if this isn't a valid group.
+    pub group: u16,
+    /// Percentage of full scale clipped off. Overdrives with a cut outside of [`Self::CUT_RANGE`]
+    /// can't be read or written, since pxtone refuses to load them.
+    pub cut: f32,
+    /// Gain applied after clipping. Overdrives with an amp outside of [`Self::AMP_RANGE`] can't be
+    /// read or written, since pxtone refuses to load them.
+    pub amp: f32,
+}
+
+impl Overdrive {
+    /// Size of overdrive block data.
+    pub(crate) const SIZE: usize = 16;
+
+    /// Range of cut percentages that pxtone will load.
+    pub const CUT_RANGE: RangeInclusive<f32> = 50.0..=99.0;
+    /// Range of amps that pxtone will load.
+    pub const AMP_RANGE: RangeInclusive<f32> = 0.1..=8.0;
+
+    /// Creates an overdrive on the given group with the given cut (%) and amp.
+    pub fn new(group: u16, cut: f32, amp: f32) -> Self {
+        Self { group, cut, amp }
+    }
+
+    /// Returns the peak 16-bit sample value left after clipping.
+    ///
+    /// ```
+    /// # use ptcrab::project::Overdrive;
+    /// assert_eq!(Overdrive::new(0, 90., 2.).clip_level(), 3276);
+    /// ```
+    pub fn clip_level(&self) -> i32 {
+        (32767. * (100. - self.cut) / 100.) as i32
+    }
+
+    /// Returns whether the cut & amp are within the ranges that pxtone will load.
+    pub(crate) fn is_in_range(&self) -> bool {
+        Self::CUT_RANGE.contains(&self.cut) && Self::AMP_RANGE.contains(&self.amp)
+    }
+}
+
+impl FromRead<Self> for Overdrive {
+    type Error = ProjectError;
+
+    fn from_read<R: Read>(source: &mut R) -> Result<Self, Self::Error> {
+        let reserved_1 = u16::from_read(source)?;
+        let group = u16::from_read(source)?;
+        let cut = f32::from_read(source)?;
+        let amp = f32::from_read(source)?;
+        let reserved_2 = f32::from_read(source)?;
+        if (reserved_1 != 0) || (reserved_2 != 0.) {
+            return Err(ProjectError::Invalid);
+        }
+        let overdrive = Self { group, cut, amp };
+        if !overdrive.is_in_range() {
+            return Err(ProjectError::Invalid);
+        }
+
+        Ok(overdrive)
+    }
+}
+
+impl WriteTo for Overdrive {
+    type Error = ProjectError;
+
+    fn write_to<W: Write + Seek>(&self, sink: &mut W) -> Result<u64, Self::Error> {
+        if !self.is_in_range() {
+            return Err(ProjectError::Invalid);
+        }
+        let start_pos = 0_u16.write_to(sink)?;
+        self.group.write_to(sink)?;
+        self.cut.write_to(sink)?;
+        self.amp.write_to(sink)?;
+        0_f32.write_to(sink)?;
+
+        Ok(start_pos)
+    }
+}
@@ -0,0 +1,53 @@
+use crate::ptnoise::PtnError;
+use crate::ptvoice::PtvError;
+
+use std::io::Error as IoError;
+use thiserror::Error as ThisError;
+
+//--------------------------------------------------------------------------------------------------
+
+/// Errors arising from project operations.
+#[derive(Debug, ThisError)]
+pub enum ProjectError {
+    /// Project (or one of its voices) has a different version than supported.
+    #[error("unsupported project format")]
+    Unsupported,
+    /// Project data is malformed or contains an illegal value.
+    #[error("invalid project data")]
+    Invalid,
+    /// Project data has too many items or is too long to encode its own length.
+    #[error("project data exceeds max size")]
+    Oversized,
+
+    /// I/O error while reading/writing project data.
+    #[error("project I/O failure: {0}")]
+    IoFailure(IoError),
+}
+
+impl From<IoError> for ProjectError {
+    fn from(value: IoError) -> Self {
+        Self::IoFailure(value)
+    }
+}
+
+impl From<PtvError> for ProjectError {
+    fn from(value: PtvError) -> Self {
+        match value {
+            PtvError::Unsupported => Self::Unsupported,
+            PtvError::Invalid => Self::Invalid,
+            PtvError::Oversized => Self::Oversized,
+            PtvError::IoFailure(error) => Self::IoFailure(error),
+        }
+    }
+}
+
+impl From<PtnError> for ProjectError {
+    fn from(value: PtnError) -> Self {
+        match value {
+            PtnError::Unsupported => Self::Unsupported,
+            PtnError::Invalid => Self::Invalid,
+            PtnError::Oversized => Self::Oversized,
+            PtnError::IoFailure(error) => Self::IoFailure(error),
+        }
+    }
+}
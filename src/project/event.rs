@@ -0,0 +1,212 @@
+use super::ProjectError;
+use crate::data::{FromRead, FromReadVar, WriteTo, WriteVarTo};
+use crate::{Key, PanVolume, Tuning, Volume};
+
+use std::cmp::Ordering;
+use std::io::{Read, Seek, Write};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Single change to a unit's state at a particular point in time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    /// Absolute clock at which the event occurs.
+    pub clock: i32,
+    /// Index of the affected unit.
+    pub unit: u8,
+    /// Kind of change, along with its value.
+    pub kind: EventKind,
+}
+
+impl Event {
+    /// Creates an event at the given clock for the given unit.
+    pub fn new(clock: i32, unit: u8, kind: EventKind) -> Self {
+        Self { clock, unit, kind }
+    }
+
+    /// Orders events the way pxtone does: by clock, then by kind for events at the same clock.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind};
+    /// # use ptcrab::Key;
+    /// let on = Event::new(0, 0, EventKind::On(480));
+    /// let key = Event::new(0, 0, EventKind::Key(Key::A4));
+    /// // Keys are set before notes start.
+    /// assert!(key.cmp_order(&on).is_lt());
+    /// assert!(on.cmp_order(&Event::new(480, 0, EventKind::Key(Key::A4))).is_lt());
+    /// ```
+    pub fn cmp_order(&self, other: &Self) -> Ordering {
+        self.clock
+            .cmp(&other.clock)
+            .then_with(|| self.kind.priority().cmp(&other.kind.priority()))
+    }
+}
+
+/// Change to a unit's state, along with its value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventKind {
+    /// Starts a note lasting the given number of clocks.
+    On(i32),
+    /// Sets the key of current & future notes.
+    Key(Key),
+    /// Sets stereo channel volumes.
+    PanVolume(PanVolume),
+    /// Sets the volume of future notes.
+    Velocity(Volume),
+    /// Sets the volume of current & future notes.
+    Volume(Volume),
+    /// Sets the number of clocks taken to slide between keys.
+    Portamento(i32),
+    /// Sets the index of the voice played by future notes.
+    Voice(i32),
+    /// Sets the index of the group the unit is mixed into.
+    Group(i32),
+    /// Sets the tuning of current & future notes.
+    Tuning(Tuning),
+    /// Sets stereo panning by delaying one channel; 0 = full left, 64 = no delay, 128 = full
+    /// right.
+    PanTime(i32),
+    /// Event kind not otherwise supported (such as legacy timing events), kept as-is.
+    Other {
+        /// pxtone's event kind ID.
+        id: u8,
+        /// Raw event value.
+        value: i32,
+    },
+}
+
+impl EventKind {
+    const ON: u8 = 1;
+    const KEY: u8 = 2;
+    const PAN_VOLUME: u8 = 3;
+    const VELOCITY: u8 = 4;
+    const VOLUME: u8 = 5;
+    const PORTAMENTO: u8 = 6;
+    const VOICE: u8 = 12;
+    const GROUP: u8 = 13;
+    const TUNING: u8 = 14;
+    const PAN_TIME: u8 = 15;
+
+    /// Converts from pxtone's event kind ID and raw event value.
+    pub fn from_raw(id: u8, value: i32) -> Self {
+        match id {
+            Self::ON => Self::On(value),
+            Self::KEY => Self::Key(value.into()),
+            Self::PAN_VOLUME => Self::PanVolume(value.into()),
+            Self::VELOCITY => Self::Velocity(value.into()),
+            Self::VOLUME => Self::Volume(value.into()),
+            Self::PORTAMENTO => Self::Portamento(value),
+            Self::VOICE => Self::Voice(value),
+            Self::GROUP => Self::Group(value),
+            // Tuning is stored as the bits of an `f32`.
+            Self::TUNING => Self::Tuning(f32::from_bits(value as u32).into()),
+            Self::PAN_TIME => Self::PanTime(value),
+            _ => Self::Other { id, value },
+        }
+    }
+
+    /// Returns pxtone's event kind ID.
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::On(_) => Self::ON,
+            Self::Key(_) => Self::KEY,
+            Self::PanVolume(_) => Self::PAN_VOLUME,
+            Self::Velocity(_) => Self::VELOCITY,
+            Self::Volume(_) => Self::VOLUME,
+            Self::Portamento(_) => Self::PORTAMENTO,
+            Self::Voice(_) => Self::VOICE,
+            Self::Group(_) => Self::GROUP,
+            Self::Tuning(_) => Self::TUNING,
+            Self::PanTime(_) => Self::PAN_TIME,
+            Self::Other { id, .. } => *id,
+        }
+    }
+    /// Returns the raw event value.
+    ///
+    /// ```
+    /// # use ptcrab::project::EventKind;
+    /// assert_eq!(EventKind::Key(0x6000.into()).raw_value(), 0x6000);
+    /// assert_eq!(EventKind::Tuning(1.0.into()).raw_value(), 0x3F80_0000);
+    /// ```
+    pub fn raw_value(&self) -> i32 {
+        match self {
+            Self::On(value)
+            | Self::Portamento(value)
+            | Self::Voice(value)
+            | Self::Group(value)
+            | Self::PanTime(value)
+            | Self::Other { value, .. } => *value,
+            Self::Key(key) => key.as_value(),
+            Self::PanVolume(pan_volume) => pan_volume.as_value(),
+            Self::Velocity(volume) | Self::Volume(volume) => volume.as_value(),
+            Self::Tuning(tuning) => f32::from(*tuning).to_bits() as i32,
+        }
+    }
+
+    /// Returns the order in which pxtone processes events of this kind at the same clock.
+    fn priority(&self) -> u8 {
+        const PRIORITIES: [u8; 16] = [0, 50, 40, 60, 70, 80, 30, 0, 0, 0, 0, 255, 10, 20, 90, 100];
+
+        PRIORITIES.get(self.id() as usize).copied().unwrap_or(255)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Reads the contents of an event list block.
+pub(crate) fn read_list<R: Read>(source: &mut R) -> Result<Box<[Event]>, ProjectError> {
+    // pxtone doesn't calculate this correctly, so events are read by count instead.
+    let _size = i32::from_read(source)?;
+    let event_count = i32::from_read(source)?;
+
+    // Clocks are stored relative to the previous event.
+    let mut clock = 0_i32;
+    (0..event_count)
+        .map(|_| {
+            clock = clock.wrapping_add(i32::from_read_var(source)?);
+            let unit = u8::from_read(source)?;
+            let id = u8::from_read(source)?;
+            let value = i32::from_read_var(source)?;
+
+            Ok(Event::new(clock, unit, EventKind::from_raw(id, value)))
+        })
+        .collect()
+}
+
+/// Writes the contents of an event list block, sorting events the way pxtone does.
+pub(crate) fn write_list<W: Write + Seek>(
+    sink: &mut W,
+    events: &[Event],
+) -> Result<u64, ProjectError> {
+    let mut events: Vec<_> = events.iter().collect();
+    events.sort_by(|a, b| a.cmp_order(b));
+
+    // pxtone sizes each event as if its clock were absolute, which is replicated here so that
+    // written projects match pxtone's byte-for-byte.
+    let size = events.iter().fold(4, |size, event| {
+        size + var_len(event.clock) + 2 + var_len(event.kind.raw_value())
+    });
+    let start_pos = i32::try_from(size)
+        .map_err(|_| ProjectError::Oversized)?
+        .write_to(sink)?;
+    i32::try_from(events.len())
+        .map_err(|_| ProjectError::Oversized)?
+        .write_to(sink)?;
+
+    let mut prev_clock = 0_i32;
+    for event in events {
+        event.clock.wrapping_sub(prev_clock).write_var_to(sink)?;
+        event.unit.write_to(sink)?;
+        event.kind.id().write_to(sink)?;
+        event.kind.raw_value().write_var_to(sink)?;
+        prev_clock = event.clock;
+    }
+
+    Ok(start_pos)
+}
+
+/// Returns the number of bytes needed to write a value using variable-length encoding.
+fn var_len(value: i32) -> usize {
+    let bits = 32 - (value as u32).leading_zeros() as usize;
+    bits.div_ceil(7).max(1)
+}
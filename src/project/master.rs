@@ -0,0 +1,126 @@
+use super::{ProjectError, Timing};
+use crate::data::{FromRead, WriteTo};
+
+use std::io::{Read, Seek, Write};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Project-wide timing settings.
+///
+/// pxtone measures time in "clock" ticks, where each beat is divided into [`Self::beat_clock`]
+/// ticks and each measure is made up of [`Self::beats_per_measure`] beats.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Master {
+    /// Number of clock ticks per beat.
+    pub beat_clock: u16,
+    /// Number of beats per measure.
+    pub beats_per_measure: u8,
+    /// Tempo in beats per minute.
+    pub tempo: f32,
+    /// Clock at which playback restarts after reaching the end of the project. pxtone rounds this
+    /// down to the start of a measure.
+    pub repeat_clock: i32,
+    /// Clock at which the project ends, or [`None`] to end after the last event. pxtone rounds
+    /// this down to the start of a measure.
+    pub last_clock: Option<i32>,
+}
+
+impl Master {
+    /// Size of master block data.
+    pub(crate) const SIZE: usize = 15;
+
+    /// Creates timing settings with the given beat clock, beats per measure and tempo (in beats
+    /// per minute), without loop points.
+    pub fn new(beat_clock: u16, beats_per_measure: u8, tempo: f32) -> Self {
+        Self {
+            beat_clock,
+            beats_per_measure,
+            tempo,
+            repeat_clock: 0,
+            last_clock: None,
+        }
+    }
+
+    /// Returns conversions between positions, clocks, beats, measures, seconds & sample frames at
+    /// the given sample rate.
+    pub fn timing(&self, sample_rate: u32) -> Timing {
+        Timing::new(self, sample_rate)
+    }
+
+    /// Returns the number of clock ticks per measure.
+    ///
+    /// ```
+    /// # use ptcrab::project::Master;
+    /// assert_eq!(Master::new(480, 4, 120.).measure_clock(), 1920);
+    /// ```
+    pub fn measure_clock(&self) -> i32 {
+        (self.beat_clock as i32) * (self.beats_per_measure as i32)
+    }
+
+    /// Returns the measure at which playback restarts after reaching the end of the project.
+    pub fn repeat_measure(&self) -> i32 {
+        self.repeat_clock / self.measure_clock().max(1)
+    }
+    /// Sets the measure at which playback restarts after reaching the end of the project. The
+    /// clock saturates at the bounds of [`i32`].
+    pub fn set_repeat_measure(&mut self, measure: i32) {
+        self.repeat_clock = measure.saturating_mul(self.measure_clock());
+    }
+
+    /// Returns the measure at which the project ends, if set.
+    pub fn last_measure(&self) -> Option<i32> {
+        self.last_clock
+            .map(|clock| clock / self.measure_clock().max(1))
+    }
+    /// Sets the measure at which the project ends, or [`None`] to end after the last event. The
+    /// clock saturates at the bounds of [`i32`].
+    ///
+    /// ```
+    /// # use ptcrab::project::Master;
+    /// let mut master = Master::new(480, 4, 120.);
+    /// master.set_last_measure(Some(8));
+    /// assert_eq!(master.last_clock, Some(8 * 1920));
+    /// assert_eq!(master.last_measure(), Some(8));
+    ///
+    /// master.set_last_measure(Some(2_000_000));
+    /// assert_eq!(master.last_clock, Some(i32::MAX));
+    /// ```
+    pub fn set_last_measure(&mut self, measure: Option<i32>) {
+        self.last_clock = measure.map(|measure| measure.saturating_mul(self.measure_clock()));
+    }
+}
+
+impl FromRead<Self> for Master {
+    type Error = ProjectError;
+
+    fn from_read<R: Read>(source: &mut R) -> Result<Self, Self::Error> {
+        Ok(Self {
+            beat_clock: u16::from_read(source)?,
+            beats_per_measure: u8::from_read(source)?,
+            tempo: f32::from_read(source)?,
+            repeat_clock: i32::from_read(source)?,
+            // A last clock of 0 means the project ends after its last event.
+            last_clock: Some(i32::from_read(source)?).filter(|&clock| clock != 0),
+        })
+    }
+}
+
+impl WriteTo for Master {
+    type Error = ProjectError;
+
+    fn write_to<W: Write + Seek>(&self, sink: &mut W) -> Result<u64, Self::Error> {
+        let start_pos = self.beat_clock.write_to(sink)?;
+        self.beats_per_measure.write_to(sink)?;
+        self.tempo.write_to(sink)?;
+        self.repeat_clock.write_to(sink)?;
+        self.last_clock.unwrap_or(0).write_to(sink)?;
+
+        Ok(start_pos)
+    }
+}
+
+impl Default for Master {
+    fn default() -> Self {
+        Self::new(480, 4, 120.)
+    }
+}
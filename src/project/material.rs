@@ -0,0 +1,190 @@
+//! Project voice ("material") blocks.
+
+use super::{block, BlockTag, ProjectError};
+use crate::data::{FromRead, WriteTo};
+use crate::ptnoise::Ptnoise;
+use crate::ptvoice::Ptvoice;
+use crate::voice::{OggVoice, PcmVoice, PtnVoice, Voice, VoiceFlags};
+use crate::Key;
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Reads a voice from the data of a material block with the given tag.
+pub(crate) fn read(tag: &BlockTag, mut data: &[u8]) -> Result<Voice, ProjectError> {
+    let source = &mut data;
+
+    match *tag {
+        block::PTV => {
+            // Legacy unit number & tuning are unused.
+            let _ = u16::from_read(source)?;
+            if u16::from_read(source)? != 0 {
+                return Err(ProjectError::Invalid);
+            }
+            let _ = f32::from_read(source)?;
+            let _size = i32::from_read(source)?;
+
+            Ok(Ptvoice::from_read(source)?.into())
+        }
+        block::PTN => {
+            // Legacy unit number is unused.
+            let _ = u16::from_read(source)?;
+            let inherent_key = read_basic_key(source)?;
+            let flags = read_flags(source)?;
+            let tuning = f32::from_read(source)?.into();
+            if !(0..=1).contains(&i32::from_read(source)?) {
+                return Err(ProjectError::Invalid);
+            }
+
+            Ok(PtnVoice {
+                inherent_key,
+                tuning,
+                flags,
+
+                ptnoise: Ptnoise::from_read(source)?,
+            }
+            .into())
+        }
+        block::PCM => {
+            // Legacy unit number is unused.
+            let _ = u16::from_read(source)?;
+            let inherent_key = read_basic_key(source)?;
+            let flags = read_flags(source)?;
+            let channels = u16::from_read(source)?;
+            let bits_per_sample = u16::from_read(source)?;
+            let sample_rate = u32::from_read(source)?;
+            let tuning = f32::from_read(source)?.into();
+            let data_size = u32::from_read(source)? as usize;
+            let data = source.get(..data_size).ok_or(ProjectError::Invalid)?;
+
+            Ok(PcmVoice {
+                inherent_key,
+                tuning,
+                flags,
+
+                channels,
+                sample_rate,
+                bits_per_sample,
+                data: data.into(),
+            }
+            .into())
+        }
+        block::OGG => {
+            // Reserved value is unused.
+            let _ = u16::from_read(source)?;
+            let inherent_key = read_basic_key(source)?;
+            let flags = read_flags(source)?;
+            let tuning = f32::from_read(source)?.into();
+            let mut read_u32 =
+                || u32::try_from(i32::from_read(source)?).map_err(|_| ProjectError::Invalid);
+            let channels = read_u32()?;
+            let sample_rate = read_u32()?;
+            let sample_count = read_u32()?;
+            let data_size = read_u32()? as usize;
+            let data = source.get(..data_size).ok_or(ProjectError::Invalid)?;
+
+            Ok(OggVoice {
+                inherent_key,
+                tuning,
+                flags,
+
+                channels,
+                sample_rate,
+                sample_count,
+                data: data.into(),
+            }
+            .into())
+        }
+        _ => Err(ProjectError::Invalid),
+    }
+}
+
+/// Writes a voice as a material block.
+pub(crate) fn write<W: Write + Seek>(sink: &mut W, voice: &Voice) -> Result<u64, ProjectError> {
+    match voice {
+        Voice::Ptv(ptv) => block::write(sink, &block::PTV, |sink| {
+            // Legacy unit number, reserved value & legacy tuning.
+            0_u16.write_to(sink)?;
+            0_u16.write_to(sink)?;
+            0_f32.write_to(sink)?;
+            // Placeholder for ptvoice size (to be written later).
+            let size_pos = 0_i32.write_to(sink)?;
+
+            let ptv_start = ptv.write_to(sink)?;
+
+            // Go back to update ptvoice size.
+            let ptv_end = sink.stream_position()?;
+            let size = i32::try_from(ptv_end - ptv_start).map_err(|_| ProjectError::Oversized)?;
+            sink.seek(SeekFrom::Start(size_pos))?;
+            size.write_to(sink)?;
+            sink.seek(SeekFrom::Start(ptv_end))?;
+
+            Ok(())
+        }),
+        Voice::Ptn(ptn) => block::write(sink, &block::PTN, |sink| {
+            // Legacy unit number.
+            0_u16.write_to(sink)?;
+            write_basic_key(sink, ptn.inherent_key)?;
+            ptn.flags.write_to(sink)?;
+            f32::from(ptn.tuning).write_to(sink)?;
+            // pxtone always writes 1 here.
+            1_i32.write_to(sink)?;
+
+            ptn.ptnoise.write_to(sink)?;
+            Ok(())
+        }),
+        Voice::Pcm(pcm) => block::write(sink, &block::PCM, |sink| {
+            // Legacy unit number.
+            0_u16.write_to(sink)?;
+            write_basic_key(sink, pcm.inherent_key)?;
+            pcm.flags.write_to(sink)?;
+            pcm.channels.write_to(sink)?;
+            pcm.bits_per_sample.write_to(sink)?;
+            pcm.sample_rate.write_to(sink)?;
+            f32::from(pcm.tuning).write_to(sink)?;
+            u32::try_from(pcm.data.len())
+                .map_err(|_| ProjectError::Oversized)?
+                .write_to(sink)?;
+
+            sink.write_all(&pcm.data)?;
+            Ok(())
+        }),
+        Voice::Ogg(ogg) => block::write(sink, &block::OGG, |sink| {
+            // Reserved value.
+            0_u16.write_to(sink)?;
+            write_basic_key(sink, ogg.inherent_key)?;
+            ogg.flags.write_to(sink)?;
+            f32::from(ogg.tuning).write_to(sink)?;
+            for value in [
+                ogg.channels as usize,
+                ogg.sample_rate as usize,
+                ogg.sample_count as usize,
+                ogg.data.len(),
+            ] {
+                i32::try_from(value)
+                    .map_err(|_| ProjectError::Oversized)?
+                    .write_to(sink)?;
+            }
+
+            sink.write_all(&ogg.data)?;
+            Ok(())
+        }),
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+fn read_basic_key<R: Read>(source: &mut R) -> Result<Key, ProjectError> {
+    Ok(Key::from_basic(u16::from_read(source)?.into()))
+}
+
+fn write_basic_key<W: Write + Seek>(sink: &mut W, key: Key) -> Result<u64, ProjectError> {
+    Ok(u16::try_from(key.as_basic())
+        .map_err(|_| ProjectError::Invalid)?
+        .write_to(sink)?)
+}
+
+fn read_flags<R: Read>(source: &mut R) -> Result<VoiceFlags, ProjectError> {
+    VoiceFlags::from_read(source)?.ok_or(ProjectError::Invalid)
+}
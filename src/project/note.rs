@@ -0,0 +1,164 @@
+use super::{Event, EventKind};
+use crate::{Key, Volume};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Single note played by a unit, as seen in pxtone Collage's piano roll.
+///
+/// Notes are a view over a unit's [`EventKind::On`], [`EventKind::Key`], [`EventKind::Velocity`]
+/// & [`EventKind::Voice`] events; see [`Note::from_events`] & [`Note::to_events`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Note {
+    /// Absolute clock at which the note starts. See [`Timing`](super::Timing) for conversions
+    /// from measures, beats & seconds.
+    pub start_clock: i32,
+    /// Number of clocks until note-off.
+    pub length: i32,
+    /// Key at the start of the note.
+    pub key: Key,
+    /// Velocity of the note.
+    pub velocity: Volume,
+    /// Index of the voice played by the note.
+    pub voice: usize,
+}
+
+impl Note {
+    /// Key of notes played before any key event.
+    pub const DEFAULT_KEY: Key = Key::A4;
+    /// Velocity of notes played before any velocity event.
+    pub const DEFAULT_VELOCITY: Volume = Volume::from_value(104);
+    /// Voice of notes played before any voice event.
+    pub const DEFAULT_VOICE: usize = 0;
+
+    /// Creates a note at the given clock with the default velocity & voice.
+    pub fn new(start_clock: i32, length: i32, key: Key) -> Self {
+        Self {
+            start_clock,
+            length,
+            key,
+            velocity: Self::DEFAULT_VELOCITY,
+            voice: Self::DEFAULT_VOICE,
+        }
+    }
+
+    /// Returns the clock at which the note ends.
+    pub fn end_clock(&self) -> i32 {
+        self.start_clock.saturating_add(self.length)
+    }
+
+    /// Collects the notes played by the given unit, ordered by start clock. Each note takes the key,
+    /// velocity & voice in effect at its start clock, including changes made at that same clock.
+    /// Voice events with negative indices are ignored, as they are during playback.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Note};
+    /// # use ptcrab::Key;
+    /// let events = [
+    ///     Event::new(0, 0, EventKind::On(480)),
+    ///     Event::new(480, 0, EventKind::On(240)),
+    ///     Event::new(480, 0, EventKind::Key(Key::C4)),
+    ///     Event::new(480, 0, EventKind::Voice(2)),
+    ///     Event::new(480, 1, EventKind::On(960)),
+    /// ];
+    /// let notes = Note::from_events(&events, 0);
+    /// assert_eq!(notes[0], Note::new(0, 480, Key::A4));
+    /// assert_eq!(notes[1], Note { voice: 2, ..Note::new(480, 240, Key::C4) });
+    /// assert_eq!(notes.len(), 2);
+    /// ```
+    pub fn from_events(events: &[Event], unit: u8) -> Box<[Self]> {
+        let mut events: Vec<_> = events.iter().filter(|event| event.unit == unit).collect();
+        events.sort_by(|a, b| a.cmp_order(b));
+
+        let mut key = Self::DEFAULT_KEY;
+        let mut velocity = Self::DEFAULT_VELOCITY;
+        let mut voice = Self::DEFAULT_VOICE;
+
+        let mut notes = vec![];
+        // Every event at a clock is applied before notes starting at that clock are collected.
+        for events in events.chunk_by(|a, b| a.clock == b.clock) {
+            for event in events {
+                match event.kind {
+                    EventKind::Key(value) => key = value,
+                    EventKind::Velocity(value) => velocity = value,
+                    EventKind::Voice(value) => voice = usize::try_from(value).unwrap_or(voice),
+                    _ => {}
+                }
+            }
+            notes.extend(events.iter().filter_map(|event| match event.kind {
+                EventKind::On(length) => Some(Self {
+                    start_clock: event.clock,
+                    length,
+                    key,
+                    velocity,
+                    voice,
+                }),
+                _ => None,
+            }));
+        }
+
+        notes.into_boxed_slice()
+    }
+
+    /// Converts notes into events for the given unit, only adding key, velocity & voice events
+    /// where they differ from the previous note (or from the defaults, for the first note).
+    ///
+    /// Notes starting at the same clock share the same key, velocity & voice during playback, so
+    /// only the last of their values is kept.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Note};
+    /// # use ptcrab::Key;
+    /// let notes = [
+    ///     Note::new(0, 480, Key::A4),
+    ///     Note::new(480, 480, Key::A4),
+    ///     Note::new(960, 480, Key::C4),
+    /// ];
+    /// let events = Note::to_events(&notes, 3);
+    /// assert_eq!(
+    ///     *events,
+    ///     [
+    ///         Event::new(0, 3, EventKind::On(480)),
+    ///         Event::new(480, 3, EventKind::On(480)),
+    ///         Event::new(960, 3, EventKind::Key(Key::C4)),
+    ///         Event::new(960, 3, EventKind::On(480)),
+    ///     ]
+    /// );
+    /// assert_eq!(*Note::from_events(&events, 3), notes);
+    /// ```
+    pub fn to_events(notes: &[Self], unit: u8) -> Box<[Event]> {
+        let mut notes: Vec<_> = notes.iter().collect();
+        notes.sort_by_key(|note| note.start_clock);
+
+        let mut key = Self::DEFAULT_KEY;
+        let mut velocity = Self::DEFAULT_VELOCITY;
+        let mut voice = Self::DEFAULT_VOICE;
+
+        let mut events = vec![];
+        for notes in notes.chunk_by(|a, b| a.start_clock == b.start_clock) {
+            let clock = notes[0].start_clock;
+            let last = notes[notes.len() - 1];
+
+            if last.voice != voice {
+                voice = last.voice;
+                let value = i32::try_from(voice).unwrap_or(i32::MAX);
+                events.push(Event::new(clock, unit, EventKind::Voice(value)));
+            }
+            if last.key != key {
+                key = last.key;
+                events.push(Event::new(clock, unit, EventKind::Key(key)));
+            }
+            if last.velocity != velocity {
+                velocity = last.velocity;
+                events.push(Event::new(clock, unit, EventKind::Velocity(velocity)));
+            }
+            events.extend(
+                notes
+                    .iter()
+                    .map(|note| Event::new(clock, unit, EventKind::On(note.length))),
+            );
+        }
+        events.sort_by(Event::cmp_order);
+
+        events.into_boxed_slice()
+    }
+}
@@ -0,0 +1,191 @@
+use super::Master;
+
+use std::fmt::{self, Display, Formatter};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Position within a project in measures, beats & ticks, each counted from 0.
+///
+/// ```
+/// # use ptcrab::project::Position;
+/// assert_eq!(Position::new(2, 1, 240).to_string(), "2:1:240");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    /// Index of the measure.
+    pub measure: i32,
+    /// Index of the beat within the measure.
+    pub beat: i32,
+    /// Clock ticks since the start of the beat.
+    pub tick: i32,
+}
+
+impl Position {
+    /// Creates a position from a measure, beat & tick.
+    pub fn new(measure: i32, beat: i32, tick: i32) -> Self {
+        Self {
+            measure,
+            beat,
+            tick,
+        }
+    }
+}
+
+impl Display for Position {
+    /// Formats the position as `measure:beat:tick`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.measure, self.beat, self.tick)
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Conversions between clocks, beats, measures, seconds & sample frames, using a project's timing
+/// settings at a particular sample rate.
+///
+/// Conversions to & from sample frames are calculated the same way as pxtone, so that they line up
+/// exactly with rendered audio.
+///
+/// ```
+/// # use ptcrab::project::{Master, Position};
+/// let timing = Master::new(480, 4, 120.).timing(44100);
+/// let clock = timing.clock_at(Position::new(1, 2, 0));
+/// assert_eq!(clock, 2880);
+/// assert_eq!(timing.clock_to_beats(clock), 6.);
+/// assert_eq!(timing.clock_to_secs(clock), 3.);
+/// assert_eq!(timing.clock_to_frame(clock), 132300);
+/// assert_eq!(timing.clock_len_to_frames(480), 22050);
+/// assert_eq!(timing.position_at(timing.frame_to_clock(132300)).to_string(), "1:2:0");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timing {
+    master: Master,
+    sample_rate: u32,
+    /// Sample frames per clock tick.
+    frames_per_clock: f32,
+}
+
+impl Timing {
+    /// Prepares conversions using the given timing settings at the given sample rate.
+    pub fn new(master: &Master, sample_rate: u32) -> Self {
+        // pxtone calculates this in double precision, but stores it in single precision.
+        let frames_per_clock =
+            ((60. * sample_rate as f64) / (master.tempo as f64 * master.beat_clock as f64)) as f32;
+
+        Self {
+            master: master.clone(),
+            sample_rate,
+            frames_per_clock,
+        }
+    }
+
+    /// Returns the timing settings used for conversions.
+    pub fn master(&self) -> &Master {
+        &self.master
+    }
+    /// Returns the sample rate used for conversions to & from sample frames.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    /// Returns the tempo in beats per minute.
+    pub fn tempo(&self) -> f32 {
+        self.master.tempo
+    }
+    /// Returns the number of clock ticks per beat.
+    pub fn beat_clock(&self) -> i32 {
+        self.master.beat_clock as i32
+    }
+    /// Returns the number of clock ticks per measure.
+    pub fn measure_clock(&self) -> i32 {
+        self.master.measure_clock()
+    }
+    /// Returns the number of sample frames per clock tick.
+    pub fn frames_per_clock(&self) -> f32 {
+        self.frames_per_clock
+    }
+
+    /// Converts a position into a clock, saturating at the bounds of [`i32`].
+    ///
+    /// ```
+    /// # use ptcrab::project::{Master, Position};
+    /// let timing = Master::new(480, 4, 120.).timing(44100);
+    /// assert_eq!(timing.clock_at(Position::new(2, 1, 240)), 2 * 1920 + 480 + 240);
+    /// assert_eq!(timing.clock_at(Position::new(2_000_000, 0, 0)), i32::MAX);
+    /// ```
+    pub fn clock_at(&self, position: Position) -> i32 {
+        position
+            .measure
+            .saturating_mul(self.measure_clock())
+            .saturating_add(position.beat.saturating_mul(self.beat_clock()))
+            .saturating_add(position.tick)
+    }
+    /// Splits a clock into a position.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Master, Position};
+    /// let timing = Master::new(480, 4, 120.).timing(44100);
+    /// assert_eq!(timing.position_at(2 * 1920 + 480 + 240), Position::new(2, 1, 240));
+    /// ```
+    pub fn position_at(&self, clock: i32) -> Position {
+        let beat_clock = self.beat_clock().max(1);
+        let beats = clock.div_euclid(beat_clock);
+        let beats_per_measure = (self.master.beats_per_measure as i32).max(1);
+
+        Position::new(
+            beats.div_euclid(beats_per_measure),
+            beats.rem_euclid(beats_per_measure),
+            clock.rem_euclid(beat_clock),
+        )
+    }
+
+    /// Converts a clock into beats.
+    pub fn clock_to_beats(&self, clock: i32) -> f64 {
+        (clock as f64) / (self.master.beat_clock as f64)
+    }
+    /// Converts beats into a clock, rounding down.
+    pub fn beats_to_clock(&self, beats: f64) -> i32 {
+        (beats * (self.master.beat_clock as f64)) as i32
+    }
+    /// Converts a clock into measures.
+    pub fn clock_to_measures(&self, clock: i32) -> f64 {
+        (clock as f64) / (self.measure_clock() as f64)
+    }
+    /// Converts measures into a clock, rounding down.
+    pub fn measures_to_clock(&self, measures: f64) -> i32 {
+        (measures * (self.measure_clock() as f64)) as i32
+    }
+
+    /// Converts a clock into seconds.
+    pub fn clock_to_secs(&self, clock: i32) -> f64 {
+        (clock as f64) * 60. / ((self.master.tempo as f64) * (self.master.beat_clock as f64))
+    }
+    /// Converts seconds into a clock, rounding down.
+    pub fn secs_to_clock(&self, secs: f64) -> i32 {
+        (secs * (self.master.tempo as f64) * (self.master.beat_clock as f64) / 60.) as i32
+    }
+
+    /// Converts a clock into the sample frame at which it starts.
+    pub fn clock_to_frame(&self, clock: i32) -> i32 {
+        ((clock as f64) * (self.frames_per_clock as f64)) as i32
+    }
+    /// Converts a sample frame into the clock that it falls within.
+    pub fn frame_to_clock(&self, frame: i32) -> i32 {
+        ((frame as f32) / self.frames_per_clock) as i32
+    }
+    /// Converts a length in clock ticks (such as a note's length) into sample frames.
+    ///
+    /// Unlike [`Self::clock_to_frame`], this is calculated in single precision as pxtone does for
+    /// lengths, so the two can differ by a frame.
+    pub fn clock_len_to_frames(&self, clock_len: i32) -> i32 {
+        ((clock_len as f32) * self.frames_per_clock) as i32
+    }
+
+    /// Converts a sample frame into seconds.
+    pub fn frame_to_secs(&self, frame: i32) -> f64 {
+        (frame as f64) / (self.sample_rate as f64)
+    }
+    /// Converts seconds into a sample frame, rounding down.
+    pub fn secs_to_frame(&self, secs: f64) -> i32 {
+        (secs * (self.sample_rate as f64)) as i32
+    }
+}
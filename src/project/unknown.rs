@@ -0,0 +1,37 @@
+//--------------------------------------------------------------------------------------------------
+
+/// Project block not understood by ptcrab, kept as-is so that it can be written back out unchanged.
+///
+/// ```
+/// # use ptcrab::data::{FromRead, WriteTo};
+/// # use ptcrab::project::{Project, ProjectError, UnknownBlock};
+/// # use std::io::Cursor;
+/// let mut project = Project::default();
+/// project.unknown_blocks = Box::new([UnknownBlock {
+///     tag: *b"antiOPER",
+///     position: 1,
+///     data: Box::new([0x12, 0x34, 0x56]),
+/// }]);
+/// let mut data = Cursor::new(vec![]);
+/// project.write_to(&mut data)?;
+///
+/// // The unknown block is read back & written out again byte-for-byte.
+/// data.set_position(0);
+/// let read = Project::from_read(&mut data)?;
+/// assert_eq!(read.unknown_blocks, project.unknown_blocks);
+/// let mut rewritten = Cursor::new(vec![]);
+/// read.write_to(&mut rewritten)?;
+/// assert_eq!(rewritten.into_inner(), data.into_inner());
+/// # Ok::<(), ProjectError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnknownBlock {
+    /// Tag identifying the block's contents.
+    pub tag: [u8; 8],
+    /// Number of known blocks preceding this one. When written, the block is placed after this many
+    /// known blocks (or just before the end marker if there are fewer), following any earlier
+    /// unknown blocks with the same position.
+    pub position: usize,
+    /// Raw block data, excluding the tag and size.
+    pub data: Box<[u8]>,
+}
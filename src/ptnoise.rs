@@ -0,0 +1,120 @@
+//! pxtone noise (ptnoise) functionality.
+
+mod error;
+mod oscillator;
+mod render;
+mod unit;
+pub use self::error::*;
+pub use self::oscillator::*;
+pub use self::unit::*;
+
+use crate::data::{FromRead, FromReadVar, WriteTo, WriteVarTo};
+
+use std::io::SeekFrom;
+use std::io::{Read, Seek, Write};
+
+//--------------------------------------------------------------------------------------------------
+
+type PtnSignature = [u8; 8];
+
+/// Synthesized sound effect made up of modulated oscillators, which pxtone renders to PCM ahead of
+/// playback.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ptnoise {
+    /// Length of the noise in samples at 44.1 kHz. pxtone clamps this to 480000 (10 seconds at
+    /// 48 kHz).
+    pub sample_count: i32,
+    /// Units mixed together to create the noise. pxtone will refuse to load a ptnoise with more
+    /// than 4 enabled units.
+    pub units: Box<[PtnUnit]>,
+}
+
+impl Ptnoise {
+    /// String present at the start of ptnoise data.
+    const SIGNATURE: PtnSignature = *b"PTNOISE-";
+    /// Maximum supported format version.
+    #[allow(clippy::inconsistent_digit_grouping)]
+    const VERSION: u32 = 2012_04_18;
+
+    /// Maximum number of units that pxtone will load.
+    pub const MAX_UNITS: usize = 4;
+
+    /// Creates a ptnoise with the given length (in samples at 44.1 kHz) and units.
+    pub fn new(sample_count: i32, units: Box<[PtnUnit]>) -> Self {
+        Self {
+            sample_count,
+            units,
+        }
+    }
+}
+
+impl FromRead<Self> for Ptnoise {
+    type Error = PtnError;
+
+    fn from_read<R: Read>(source: &mut R) -> Result<Self, Self::Error> {
+        // Check signature at start of data.
+        if Self::SIGNATURE != PtnSignature::from_read(source)? {
+            return Err(PtnError::Invalid);
+        }
+        // Check that format version is supported.
+        if Self::VERSION < u32::from_read(source)? {
+            return Err(PtnError::Unsupported);
+        }
+
+        let sample_count = i32::from_read_var(source)?;
+
+        // Read units...
+        let unit_count: usize = i8::from_read(source)?
+            .try_into()
+            .map_err(|_| PtnError::Invalid)?;
+        if unit_count > Self::MAX_UNITS {
+            return Err(PtnError::Invalid);
+        }
+        let units = (0..unit_count)
+            .map(|_| PtnUnit::from_read(source))
+            .collect::<Result<Box<[_]>, _>>()?;
+
+        Ok(Self {
+            sample_count,
+            units,
+        })
+    }
+}
+
+impl WriteTo for Ptnoise {
+    type Error = PtnError;
+
+    fn write_to<W: Write + Seek>(&self, sink: &mut W) -> Result<u64, Self::Error> {
+        // Ptnoise signature and format version.
+        let start_pos = Self::SIGNATURE.write_to(sink)?;
+        Self::VERSION.write_to(sink)?;
+
+        self.sample_count.write_var_to(sink)?;
+
+        // Placeholder for unit count (to be written later).
+        let unit_count_pos = 0_i8.write_to(sink)?;
+        // Only enabled units are written.
+        let mut unit_count = 0;
+        for unit in self.units.iter().filter(|unit| unit.enabled) {
+            if unit_count == Self::MAX_UNITS {
+                return Err(PtnError::Oversized);
+            }
+            unit.write_to(sink)?;
+            unit_count += 1;
+        }
+
+        // Go back to update unit count.
+        let data_end = sink.stream_position()?;
+        sink.seek(SeekFrom::Start(unit_count_pos))?;
+        (unit_count as i8).write_to(sink)?;
+        sink.seek(SeekFrom::Start(data_end))?;
+
+        Ok(start_pos)
+    }
+}
+
+impl Default for Ptnoise {
+    fn default() -> Self {
+        Self::new(44100, Box::new([PtnUnit::default()]))
+    }
+}
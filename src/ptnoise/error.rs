@@ -0,0 +1,28 @@
+use std::io::Error as IoError;
+use thiserror::Error as ThisError;
+
+//--------------------------------------------------------------------------------------------------
+
+/// Errors arising from ptnoise operations.
+#[derive(Debug, ThisError)]
+pub enum PtnError {
+    /// Ptnoise has newer version than supported.
+    #[error("unsupported ptnoise format")]
+    Unsupported,
+    /// Ptnoise data is malformed or contains an illegal value.
+    #[error("invalid ptnoise data")]
+    Invalid,
+    /// Ptnoise data has more items than pxtone can load.
+    #[error("ptnoise data exceeds max size")]
+    Oversized,
+
+    /// I/O error while reading/writing ptnoise data.
+    #[error("ptnoise I/O failure: {0}")]
+    IoFailure(IoError),
+}
+
+impl From<IoError> for PtnError {
+    fn from(value: IoError) -> Self {
+        Self::IoFailure(value)
+    }
+}
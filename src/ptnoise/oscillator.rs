@@ -0,0 +1,147 @@
+use super::PtnError;
+use crate::data::{FromRead, FromReadVar, WriteTo, WriteVarTo};
+
+use std::io::{Read, Seek, Write};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Built-in waveforms available to ptnoise oscillators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PtnWaveType {
+    Sine = 1,
+    /// Descending sawtooth.
+    Saw,
+    /// Square wave with 50% duty cycle.
+    Rect,
+    /// Random values, linearly interpolated.
+    Random,
+    /// Sawtooth built from 16 sine harmonics.
+    Saw2,
+    /// Square wave built from sine harmonics.
+    Rect2,
+    Triangle,
+    /// Random values, held between steps.
+    Random2,
+    /// Pulse wave with 1/3 duty cycle.
+    Rect3,
+    /// Pulse wave with 1/4 duty cycle.
+    Rect4,
+    /// Pulse wave with 1/8 duty cycle.
+    Rect8,
+    /// Pulse wave with 1/16 duty cycle.
+    Rect16,
+    /// Descending sawtooth quantized to 3 steps.
+    Saw3,
+    /// Descending sawtooth quantized to 4 steps.
+    Saw4,
+    /// Descending sawtooth quantized to 6 steps.
+    Saw6,
+    /// Descending sawtooth quantized to 8 steps.
+    Saw8,
+}
+
+impl PtnWaveType {
+    /// Every wave type, ordered by ID.
+    pub(crate) const ALL: [Self; 16] = [
+        Self::Sine,
+        Self::Saw,
+        Self::Rect,
+        Self::Random,
+        Self::Saw2,
+        Self::Rect2,
+        Self::Triangle,
+        Self::Random2,
+        Self::Rect3,
+        Self::Rect4,
+        Self::Rect8,
+        Self::Rect16,
+        Self::Saw3,
+        Self::Saw4,
+        Self::Saw6,
+        Self::Saw8,
+    ];
+    /// ID used by pxtone for an absent oscillator.
+    const NONE: i32 = 0;
+
+    /// Converts from pxtone's wave type ID, where 0 means no waveform.
+    fn from_id(id: i32) -> Result<Option<Self>, PtnError> {
+        match id {
+            Self::NONE => Ok(None),
+            1..=16 => Ok(Some(Self::ALL[(id - 1) as usize])),
+            _ => Err(PtnError::Invalid),
+        }
+    }
+}
+
+/// Ptnoise oscillator, used either as a unit's main waveform or to modulate its frequency or
+/// volume.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PtnOscillator {
+    /// Oscillator waveform.
+    pub wave_type: PtnWaveType,
+    /// Frequency in hertz. pxtone clamps this to 0–44100.
+    pub frequency: f32,
+    /// Volume (or modulation depth) as a percentage. pxtone clamps this to 0–200.
+    pub volume: f32,
+    /// Starting phase as a percentage of the waveform's period. pxtone clamps this to 0–100.
+    pub offset: f32,
+    /// Inverts the waveform.
+    pub reverse: bool,
+}
+
+impl PtnOscillator {
+    /// Creates an oscillator with the given waveform, frequency (Hz) and volume (%), with no
+    /// offset.
+    pub fn new(wave_type: PtnWaveType, frequency: f32, volume: f32) -> Self {
+        Self {
+            wave_type,
+            frequency,
+            volume,
+            offset: 0.,
+            reverse: false,
+        }
+    }
+}
+
+impl FromRead<Option<Self>> for PtnOscillator {
+    type Error = PtnError;
+
+    /// Reads an oscillator, returning [`None`] if its waveform is absent.
+    fn from_read<R: Read>(source: &mut R) -> Result<Option<Self>, Self::Error> {
+        let wave_type = PtnWaveType::from_id(i32::from_read_var(source)?)?;
+        let reverse = i32::from_read_var(source)? != 0;
+        // Values are stored in tenths.
+        let frequency = (i32::from_read_var(source)? as f32) / 10.;
+        let volume = (i32::from_read_var(source)? as f32) / 10.;
+        let offset = (i32::from_read_var(source)? as f32) / 10.;
+
+        Ok(wave_type.map(|wave_type| Self {
+            wave_type,
+            frequency,
+            volume,
+            offset,
+            reverse,
+        }))
+    }
+}
+
+impl WriteTo for PtnOscillator {
+    type Error = PtnError;
+
+    fn write_to<W: Write + Seek>(&self, sink: &mut W) -> Result<u64, Self::Error> {
+        let start_pos = (self.wave_type as i32).write_var_to(sink)?;
+        (self.reverse as i32).write_var_to(sink)?;
+        // Values are stored in tenths.
+        for value in [self.frequency, self.volume, self.offset] {
+            ((value * 10.).round() as i32).write_var_to(sink)?;
+        }
+
+        Ok(start_pos)
+    }
+}
+
+impl Default for PtnOscillator {
+    fn default() -> Self {
+        Self::new(PtnWaveType::Sine, 440., 100.)
+    }
+}
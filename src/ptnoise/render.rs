@@ -0,0 +1,383 @@
+use super::{PtnOscillator, PtnUnit, PtnWaveType, Ptnoise};
+use crate::render::tone::key_freq;
+use crate::render::Sample;
+
+use std::f64::consts::TAU;
+use std::sync::OnceLock;
+
+//--------------------------------------------------------------------------------------------------
+
+/// Sample rate that noise designs are defined relative to.
+const BASE_SAMPLE_RATE: f64 = 44100.;
+/// Frequency of a single period of a waveform table at the base sample rate.
+const BASE_FREQUENCY: f64 = 100.;
+/// Maximum sample value.
+const SAMPLE_TOP: i32 = 32767;
+/// Maximum frequency modulation (50 semitones) as a key offset.
+const KEY_TOP: f64 = 0x3200 as f64;
+
+/// Length of each waveform table.
+const TABLE_LEN: usize = (BASE_SAMPLE_RATE / BASE_FREQUENCY) as usize;
+/// Length of the random value table.
+const RANDOM_TABLE_LEN: usize = 44100;
+
+impl Ptnoise {
+    /// Renders the noise to interleaved samples with the given sample rate and number of channels
+    /// (1 or 2), reproducing pxtone's noise builder.
+    ///
+    /// Output is fully deterministic; "random" waveforms are generated from a fixed seed.
+    ///
+    /// ```
+    /// # use ptcrab::ptnoise::Ptnoise;
+    /// // One second of noise at 22.05 kHz, in stereo.
+    /// let noise = Ptnoise::default();
+    /// let samples = noise.render::<i16>(22050, 2);
+    /// assert_eq!(samples.len(), 2 * 22050);
+    /// ```
+    pub fn render<S: Sample>(&self, sample_rate: u32, channels: u16) -> Box<[S]> {
+        let tables = Tables::get();
+        let channels = channels as usize;
+
+        let mut units = self
+            .units
+            .iter()
+            .filter(|unit| unit.enabled)
+            .map(|unit| UnitState::new(unit, sample_rate, tables))
+            .collect::<Vec<_>>();
+
+        let sample_count = self.sample_count.clamp(0, 480000) as f64;
+        let len = (sample_count / (BASE_SAMPLE_RATE / sample_rate as f64)) as usize;
+        let mut output = Vec::with_capacity(len * channels);
+        for _ in 0..len {
+            for ch in 0..channels {
+                let work = units.iter().map(|unit| unit.sample(ch.min(1))).sum::<f64>() as i32;
+                output.push(S::from_i16(work.clamp(-SAMPLE_TOP, SAMPLE_TOP) as i16));
+            }
+            for unit in units.iter_mut() {
+                unit.increment(tables);
+            }
+        }
+
+        output.into_boxed_slice()
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Waveform tables shared by every noise render.
+struct Tables {
+    /// Periodic waveform tables, indexed by wave type ID - 1.
+    waves: [Box<[i16]>; 16],
+    /// Random values.
+    random: Box<[i16]>,
+}
+
+impl Tables {
+    /// Returns the waveform tables, building them on first use.
+    fn get() -> &'static Self {
+        static TABLES: OnceLock<Tables> = OnceLock::new();
+
+        TABLES.get_or_init(Self::build)
+    }
+
+    fn build() -> Self {
+        let top = SAMPLE_TOP as f64;
+        let from_fn = |f: &dyn Fn(usize) -> f64| -> Box<[i16]> {
+            (0..TABLE_LEN)
+                .map(|s| f(s).clamp(-top, top) as i16)
+                .collect()
+        };
+        // Sum of sine harmonics with amplitudes inversely proportional to harmonic number.
+        let harmonics = |harmonic_nums: &[usize]| {
+            from_fn(&|s| {
+                harmonic_nums
+                    .iter()
+                    .map(|&n| (TAU * (n * s) as f64 / TABLE_LEN as f64).sin() / n as f64)
+                    .sum::<f64>()
+                    * top
+            })
+        };
+        let pulse = |divisor: usize| from_fn(&|s| if s < TABLE_LEN / divisor { top } else { -top });
+        let steps = |count: usize| {
+            from_fn(&|s| {
+                let step = (s * count / TABLE_LEN) as f64;
+                top - 2. * top * step / (count - 1) as f64
+            })
+        };
+
+        let mut rng = Random::default();
+        let random = (0..RANDOM_TABLE_LEN)
+            .map(|_| rng.next())
+            .collect::<Box<[_]>>();
+
+        let waves = PtnWaveType::ALL.map(|wave_type| match wave_type {
+            PtnWaveType::Sine => from_fn(&|s| (TAU * s as f64 / TABLE_LEN as f64).sin() * top),
+            PtnWaveType::Saw => from_fn(&|s| top - (s as f64) * 2. * top / TABLE_LEN as f64),
+            PtnWaveType::Rect => pulse(2),
+            // Random waveforms read from the random table instead.
+            PtnWaveType::Random | PtnWaveType::Random2 => random[..TABLE_LEN].into(),
+            PtnWaveType::Saw2 => harmonics(&(1..=16).collect::<Vec<_>>()),
+            PtnWaveType::Rect2 => harmonics(&(1..=16).step_by(2).collect::<Vec<_>>()),
+            PtnWaveType::Triangle => from_fn(&|s| {
+                let quarter = TABLE_LEN as f64 / 4.;
+                let x = s as f64;
+                if x < quarter {
+                    top * x / quarter
+                } else if x < 3. * quarter {
+                    top - 2. * top * (x - quarter) / (2. * quarter)
+                } else {
+                    -top + top * (x - 3. * quarter) / quarter
+                }
+            }),
+            PtnWaveType::Rect3 => pulse(3),
+            PtnWaveType::Rect4 => pulse(4),
+            PtnWaveType::Rect8 => pulse(8),
+            PtnWaveType::Rect16 => pulse(16),
+            PtnWaveType::Saw3 => steps(3),
+            PtnWaveType::Saw4 => steps(4),
+            PtnWaveType::Saw6 => steps(6),
+            PtnWaveType::Saw8 => steps(8),
+        });
+
+        Self { waves, random }
+    }
+}
+
+/// pxtone's pseudo-random number generator, which swaps the bytes of a running sum.
+struct Random([u16; 2]);
+
+impl Random {
+    fn next(&mut self) -> i16 {
+        let sum = self.0[0].wrapping_add(self.0[1]).swap_bytes();
+        self.0 = [sum, self.0[0]];
+
+        sum as i16
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self([0x4444, 0x8888])
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// How an oscillator reads from the random table, if at all.
+#[derive(Clone, Copy, PartialEq)]
+enum RandomMode {
+    None,
+    /// Linearly interpolates between random values.
+    Saw,
+    /// Holds each random value.
+    Rect,
+}
+
+/// Playback state of a single oscillator.
+struct OscillatorState {
+    table: &'static [i16],
+    random_mode: RandomMode,
+    /// Table step per output sample.
+    increment: f64,
+    /// Position within the table.
+    offset: f64,
+    volume: f64,
+    reverse: bool,
+
+    random_start: i32,
+    random_margin: i32,
+    random_index: usize,
+}
+
+impl OscillatorState {
+    fn new(oscillator: Option<&PtnOscillator>, sample_rate: u32, tables: &'static Tables) -> Self {
+        // Absent oscillators behave like a silent waveform.
+        let Some(oscillator) = oscillator else {
+            return Self {
+                table: &[],
+                random_mode: RandomMode::None,
+                increment: 0.,
+                offset: 0.,
+                volume: 0.,
+                reverse: false,
+
+                random_start: 0,
+                random_margin: 0,
+                random_index: 0,
+            };
+        };
+
+        let frequency = oscillator.frequency.clamp(0., 44100.) as f64;
+        let volume = oscillator.volume.clamp(0., 200.) as f64;
+        let offset = oscillator.offset.clamp(0., 100.) as f64;
+
+        let random_mode = match oscillator.wave_type {
+            PtnWaveType::Random => RandomMode::Saw,
+            PtnWaveType::Random2 => RandomMode::Rect,
+            _ => RandomMode::None,
+        };
+        let random_index =
+            ((RANDOM_TABLE_LEN as f64 * offset / 100.) as usize).min(RANDOM_TABLE_LEN - 1);
+
+        Self {
+            table: &tables.waves[oscillator.wave_type as usize - 1],
+            random_mode,
+            increment: (BASE_SAMPLE_RATE / sample_rate as f64) * (frequency / BASE_FREQUENCY),
+            offset: match random_mode {
+                RandomMode::None => TABLE_LEN as f64 * offset / 100.,
+                _ => 0.,
+            },
+            volume: volume / 100.,
+            reverse: oscillator.reverse,
+
+            random_start: 0,
+            random_margin: tables.random[random_index] as i32,
+            random_index,
+        }
+    }
+
+    /// Returns the current value with volume & reversal applied.
+    fn value(&self) -> f64 {
+        let value = match self.random_mode {
+            RandomMode::None => {
+                let index = (self.offset as usize).min(TABLE_LEN - 1);
+                self.table.get(index).copied().unwrap_or(0) as i32
+            }
+            RandomMode::Saw => {
+                self.random_start + self.random_margin * (self.offset as i32) / TABLE_LEN as i32
+            }
+            RandomMode::Rect => self.random_start,
+        };
+        let value = if self.reverse { -value } else { value };
+
+        value as f64 * self.volume
+    }
+
+    /// Advances through the table by the given step.
+    fn increment(&mut self, increment: f64, tables: &Tables) {
+        self.offset += increment;
+        if self.offset > TABLE_LEN as f64 {
+            self.offset -= TABLE_LEN as f64;
+            if self.offset >= TABLE_LEN as f64 {
+                self.offset = 0.;
+            }
+
+            // Move on to the next random value.
+            if self.random_mode != RandomMode::None {
+                self.random_start = tables.random[self.random_index] as i32;
+                self.random_index = (self.random_index + 1) % RANDOM_TABLE_LEN;
+                self.random_margin = tables.random[self.random_index] as i32 - self.random_start;
+            }
+        }
+    }
+}
+
+/// Playback state of a single unit.
+struct UnitState {
+    /// Left & right channel volumes.
+    pan: [f64; 2],
+
+    /// Envelope points as (duration in samples, volume ratio).
+    envelope: Box<[(i32, f64)]>,
+    envelope_index: usize,
+    envelope_start: f64,
+    envelope_margin: f64,
+    envelope_count: i32,
+
+    main: OscillatorState,
+    frequency: OscillatorState,
+    volume: OscillatorState,
+}
+
+impl UnitState {
+    fn new(unit: &PtnUnit, sample_rate: u32, tables: &'static Tables) -> Self {
+        let pan = unit.pan.clamp(-100, 100) as f64;
+        let pan = if pan < 0. {
+            [1., (100. + pan) / 100.]
+        } else {
+            [(100. - pan) / 100., 1.]
+        };
+
+        // Envelope points are relative to one another.
+        let mut prev_x = 0;
+        let envelope = unit
+            .envelope
+            .iter()
+            .map(|&(x, y)| {
+                let dx = (x - prev_x).clamp(0, 10000);
+                prev_x = x;
+                (
+                    (sample_rate as i64 * dx as i64 / 1000) as i32,
+                    y.clamp(0, 100) as f64 / 100.,
+                )
+            })
+            .collect();
+
+        let mut state = Self {
+            pan,
+
+            envelope,
+            envelope_index: 0,
+            envelope_start: 0.,
+            envelope_margin: 0.,
+            envelope_count: 0,
+
+            main: OscillatorState::new(unit.main.as_ref(), sample_rate, tables),
+            frequency: OscillatorState::new(unit.frequency.as_ref(), sample_rate, tables),
+            volume: OscillatorState::new(unit.volume.as_ref(), sample_rate, tables),
+        };
+        state.skip_instant_envelope_points();
+
+        state
+    }
+
+    /// Returns the current sample for channel `ch` (0 = left, 1 = right).
+    fn sample(&self, ch: usize) -> f64 {
+        let mut work = self.main.value();
+        // Volume oscillator maps [-top, top] to [0, 1].
+        let top = SAMPLE_TOP as f64;
+        work = work * (self.volume.value() + top) / (top * 2.);
+        work *= self.pan[ch];
+
+        work * match self.envelope.get(self.envelope_index) {
+            Some(&(len, _)) => {
+                self.envelope_start
+                    + (self.envelope_margin * self.envelope_count as f64 / len as f64)
+            }
+            None => self.envelope_start,
+        }
+    }
+
+    /// Advances by one output sample.
+    fn increment(&mut self, tables: &Tables) {
+        let key_offset = (KEY_TOP * self.frequency.value() / SAMPLE_TOP as f64) as i32;
+        self.main
+            .increment(self.main.increment * key_freq(key_offset) as f64, tables);
+        self.frequency.increment(self.frequency.increment, tables);
+        self.volume.increment(self.volume.increment, tables);
+
+        if let Some(&(len, volume)) = self.envelope.get(self.envelope_index) {
+            self.envelope_count += 1;
+            if self.envelope_count >= len {
+                self.envelope_count = 0;
+                self.envelope_start = volume;
+                self.envelope_margin = 0.;
+                self.envelope_index += 1;
+                self.skip_instant_envelope_points();
+            }
+        }
+    }
+
+    /// Jumps past envelope points that take no time to reach, and prepares to move towards the
+    /// next point.
+    fn skip_instant_envelope_points(&mut self) {
+        while let Some(&(len, volume)) = self.envelope.get(self.envelope_index) {
+            self.envelope_margin = volume - self.envelope_start;
+            if len != 0 {
+                break;
+            }
+            self.envelope_start = volume;
+            self.envelope_index += 1;
+        }
+    }
+}
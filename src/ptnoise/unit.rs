@@ -0,0 +1,162 @@
+use super::{PtnError, PtnOscillator};
+use crate::data::{FromRead, FromReadVar, WriteTo, WriteVarTo};
+
+use std::io::{Read, Seek, Write};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Single ptnoise "channel", made up of a main oscillator that can be modulated by frequency and
+/// volume oscillators.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PtnUnit {
+    /// Whether this unit is written & rendered at all. Disabled units are omitted from written
+    /// ptnoise data, so units read from a ptnoise are always enabled.
+    pub enabled: bool,
+    /// List of absolute points `(x, y)` in the unit's envelope, where the x-axis represents time
+    /// in milliseconds and the y-axis represents volume as a percentage. Volume starts at 0 and the
+    /// last point is held until the end of the noise.
+    ///
+    /// **Points should be ordered by increasing x-coordinate.** pxtone will refuse to load
+    /// envelopes with more than 3 points.
+    pub envelope: Box<[(i32, i32)]>,
+    /// Stereo panning from -100 (full left) to 100 (full right).
+    pub pan: i8,
+
+    /// Main waveform. The unit is silent if this is absent.
+    pub main: Option<PtnOscillator>,
+    /// Oscillator modulating the main waveform's pitch, where 100% volume corresponds to a range
+    /// of ±50 semitones.
+    pub frequency: Option<PtnOscillator>,
+    /// Oscillator modulating the main waveform's volume. If absent, volume is halved.
+    pub volume: Option<PtnOscillator>,
+}
+
+impl PtnUnit {
+    const HAS_ENVELOPE: u32 = 1 << 2;
+    const HAS_PAN: u32 = 1 << 3;
+    const HAS_MAIN: u32 = 1 << 4;
+    const HAS_FREQUENCY: u32 = 1 << 5;
+    const HAS_VOLUME: u32 = 1 << 6;
+    const RESERVED: u32 = !(Self::HAS_ENVELOPE
+        | Self::HAS_PAN
+        | Self::HAS_MAIN
+        | Self::HAS_FREQUENCY
+        | Self::HAS_VOLUME);
+
+    /// Maximum number of envelope points that pxtone will load.
+    pub const MAX_ENVELOPE_POINTS: usize = 3;
+}
+
+impl FromRead<Self> for PtnUnit {
+    type Error = PtnError;
+
+    fn from_read<R: Read>(source: &mut R) -> Result<Self, Self::Error> {
+        let data_flags = u32::from_read_var(source)?;
+        if (data_flags & Self::RESERVED) != 0 {
+            return Err(PtnError::Invalid);
+        }
+
+        // Envelope points are stored with x-values relative to the previous point.
+        let mut envelope = vec![];
+        if (data_flags & Self::HAS_ENVELOPE) != 0 {
+            let point_count: usize = i32::from_read_var(source)?
+                .try_into()
+                .map_err(|_| PtnError::Invalid)?;
+            if point_count > Self::MAX_ENVELOPE_POINTS {
+                return Err(PtnError::Invalid);
+            }
+
+            let mut prev_x = 0;
+            for _ in 0..point_count {
+                let (dx, y) = <(i32, i32)>::from_read_var(source)?;
+                prev_x += dx;
+                envelope.push((prev_x, y));
+            }
+        }
+        let envelope = envelope.into_boxed_slice();
+
+        let pan = if (data_flags & Self::HAS_PAN) != 0 {
+            i8::from_read(source)?
+        } else {
+            0
+        };
+
+        let mut read_oscillator = |flag| match (data_flags & flag) != 0 {
+            true => PtnOscillator::from_read(source),
+            false => Ok(None),
+        };
+        let main = read_oscillator(Self::HAS_MAIN)?;
+        let frequency = read_oscillator(Self::HAS_FREQUENCY)?;
+        let volume = read_oscillator(Self::HAS_VOLUME)?;
+
+        Ok(Self {
+            enabled: true,
+            envelope,
+            pan,
+
+            main,
+            frequency,
+            volume,
+        })
+    }
+}
+
+impl WriteTo for PtnUnit {
+    type Error = PtnError;
+
+    fn write_to<W: Write + Seek>(&self, sink: &mut W) -> Result<u64, Self::Error> {
+        if self.envelope.len() > Self::MAX_ENVELOPE_POINTS {
+            return Err(PtnError::Oversized);
+        }
+
+        // pxtone always writes an envelope, but only writes panning if it's off-center.
+        let mut data_flags = Self::HAS_ENVELOPE;
+        if self.pan != 0 {
+            data_flags |= Self::HAS_PAN;
+        }
+        for (oscillator, flag) in [
+            (&self.main, Self::HAS_MAIN),
+            (&self.frequency, Self::HAS_FREQUENCY),
+            (&self.volume, Self::HAS_VOLUME),
+        ] {
+            if oscillator.is_some() {
+                data_flags |= flag;
+            }
+        }
+        let start_pos = data_flags.write_var_to(sink)?;
+
+        // Write envelope points.
+        (self.envelope.len() as i32).write_var_to(sink)?;
+        let mut prev_x = 0;
+        for &(x, y) in self.envelope.iter() {
+            (x - prev_x, y).write_var_to(sink)?;
+            prev_x = x;
+        }
+
+        if self.pan != 0 {
+            self.pan.write_to(sink)?;
+        }
+        for oscillator in [&self.main, &self.frequency, &self.volume]
+            .into_iter()
+            .flatten()
+        {
+            oscillator.write_to(sink)?;
+        }
+
+        Ok(start_pos)
+    }
+}
+
+impl Default for PtnUnit {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            envelope: Box::new([(0, 100)]),
+            pan: 0,
+
+            main: Some(PtnOscillator::default()),
+            frequency: None,
+            volume: None,
+        }
+    }
+}
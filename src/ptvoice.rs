@@ -13,6 +13,9 @@ pub use self::unit::*;
 pub use self::wave::*;
 
 use crate::data::{FromRead, FromReadVar, WriteTo, WriteVarTo};
+use crate::render::tone::{self, VoiceInstance};
+use crate::render::Sample;
+use crate::{Key, Volume};
 
 use std::io::SeekFrom;
 use std::io::{Read, Seek, Write};
@@ -49,6 +52,33 @@ impl Ptvoice {
             units,
         }
     }
+
+    /// Renders a single note as interleaved stereo samples, mixing every unit as pxtone would.
+    ///
+    /// The note is held for `length` seconds, after which each unit's envelope release plays out.
+    /// Beat-fit units are stretched to one beat at 120 BPM, pxtone's default tempo.
+    ///
+    /// ```
+    /// # use ptcrab::ptvoice::{PtvUnit, Ptvoice};
+    /// # use ptcrab::{Key, Volume};
+    /// let ptv = Ptvoice::new(Box::new([PtvUnit::default()]));
+    /// let samples = ptv.render_note::<f32>(Key::A4, Volume::from(128), 0.5, 44100);
+    /// assert_eq!(samples.len(), 2 * 22050);
+    /// ```
+    pub fn render_note<S: Sample>(
+        &self,
+        key: Key,
+        velocity: Volume,
+        length: f32,
+        sample_rate: u32,
+    ) -> Box<[S]> {
+        let instances: Box<[_]> = self
+            .units
+            .iter()
+            .map(|unit| VoiceInstance::from_ptv_unit(unit, sample_rate))
+            .collect();
+        tone::render_note(&instances, key, velocity, length, sample_rate)
+    }
 }
 
 impl FromRead<Self> for Ptvoice {
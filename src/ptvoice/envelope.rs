@@ -42,6 +42,137 @@ impl PtvEnvelope {
             ticks_per_second: 1000,
         }
     }
+
+    /// Returns the envelope gain at `time` seconds after note-on, where a gain of 1.0 corresponds
+    /// to y = 128. If `note_off` is given, the note is considered to be released that many seconds
+    /// after note-on.
+    ///
+    /// While held, volume is interpolated linearly from (0, 0) through each point in turn, then
+    /// the last point is sustained. After note-off, volume ramps linearly from its value at
+    /// note-off down to 0 over the release duration.
+    ///
+    /// An envelope without points leaves volume unchanged for the entire note, including its
+    /// release.
+    ///
+    /// ```
+    /// # use ptcrab::ptvoice::PtvEnvelope;
+    /// let envelope = PtvEnvelope::new(Box::new([(100, 128), (200, 64)]), 250);
+    /// assert_eq!(envelope.gain_at(0.05,  None), 0.5);
+    /// assert_eq!(envelope.gain_at(0.125, None), 0.875);
+    /// assert_eq!(envelope.gain_at(0.5,   None), 0.5);
+    /// // Released at 0.875 gain; halfway through the release, half of that remains.
+    /// assert_eq!(envelope.gain_at(0.25, Some(0.125)), 0.4375);
+    /// assert_eq!(envelope.gain_at(0.5,  Some(0.125)), 0.);
+    /// ```
+    pub fn gain_at(&self, time: f32, note_off: Option<f32>) -> f32 {
+        if self.points.is_empty() {
+            return 1.;
+        }
+        let ticks_per_second = self.ticks_per_second as f32;
+
+        match note_off {
+            Some(note_off) if time >= note_off => {
+                let release_start = self.held_value_at(note_off * ticks_per_second);
+                let release_pos = (time - note_off) * ticks_per_second;
+                if release_pos >= self.release as f32 {
+                    0.
+                } else {
+                    release_start * (1. - release_pos / self.release as f32) / 128.
+                }
+            }
+            _ => self.held_value_at(time * ticks_per_second) / 128.,
+        }
+    }
+    /// Returns the envelope y-value at tick `x` while a note is held.
+    fn held_value_at(&self, x: f32) -> f32 {
+        let x = x.max(0.);
+        let mut prev = (0., 0.);
+        for &(px, py) in self.points.iter() {
+            let (px, py) = (px as f32, py as f32);
+            if x < px {
+                return prev.1 + (py - prev.1) * (x - prev.0) / (px - prev.0);
+            }
+            prev = (px, py);
+        }
+
+        prev.1
+    }
+
+    /// Bakes the envelope into per-sample gains for a note held for `held_len` samples at the
+    /// given sample rate, followed by its release. A gain of 1.0 corresponds to y = 128.
+    ///
+    /// Unlike [`Self::gain_at`], this reproduces the quantization of pxtone's own envelope tables,
+    /// so the result matches pxtone's playback sample-for-sample.
+    pub fn gain_table(&self, sample_rate: u32, held_len: usize) -> Box<[f32]> {
+        let attack = self.attack_table(sample_rate);
+        let release_len = self.release_len(sample_rate);
+        let Some(&sustain) = attack.last() else {
+            return vec![1.; held_len + release_len].into_boxed_slice();
+        };
+
+        // Volume at note-off, from which the release ramps down.
+        let release_start = match held_len {
+            0 => 0,
+            _ => attack.get(held_len - 1).copied().unwrap_or(sustain) as i32,
+        };
+
+        let held = (0..held_len).map(|pos| attack.get(pos).copied().unwrap_or(sustain) as i32);
+        let release = (0..release_len)
+            .map(|pos| release_start - release_start * (pos as i32) / (release_len as i32));
+
+        held.chain(release)
+            .map(|volume| (volume as f32) / 128.)
+            .collect()
+    }
+
+    /// Builds pxtone's table of attack volumes (out of 128) at the given sample rate, one per sample
+    /// from note-on until the last point. The final value is sustained while a note is held.
+    ///
+    /// Returns an empty table if the envelope has no points.
+    pub(crate) fn attack_table(&self, sample_rate: u32) -> Box<[u8]> {
+        if self.points.is_empty() {
+            return Box::new([]);
+        }
+        let to_samples = |ticks: i32| {
+            ((ticks as f64) * (sample_rate as f64) / (self.ticks_per_second as f64)) as i32
+        };
+
+        // Convert points to sample positions. pxtone converts each relative x-value separately,
+        // and skips points that add nothing to the envelope.
+        let mut points = Vec::with_capacity(self.points.len());
+        let (mut prev_x, mut offset) = (0, 0);
+        for (i, &(x, y)) in self.points.iter().enumerate() {
+            let dx = x - prev_x;
+            prev_x = x;
+            if (i == 0) || (dx != 0) || (y != 0) {
+                offset += to_samples(dx);
+                points.push((offset, y));
+            }
+        }
+        let len = to_samples(prev_x).max(1);
+
+        // Interpolate between points, starting from (0, 0).
+        let mut start = (0, 0);
+        let mut next = points.iter().peekable();
+        (0..len)
+            .map(|pos| {
+                while let Some(&&point) = next.peek().filter(|&&&(x, _)| pos >= x) {
+                    start = point;
+                    next.next();
+                }
+                let value = match next.peek() {
+                    Some(&&(x, y)) => start.1 + (y - start.1) * (pos - start.0) / (x - start.0),
+                    None => start.1,
+                };
+                value as u8
+            })
+            .collect()
+    }
+    /// Returns release duration in samples at the given sample rate.
+    pub(crate) fn release_len(&self, sample_rate: u32) -> usize {
+        ((self.release as f64) * (sample_rate as f64) / (self.ticks_per_second as f64)).max(0.)
+            as usize
+    }
 }
 
 impl FromRead<Self> for PtvEnvelope {
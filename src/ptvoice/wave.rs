@@ -1,6 +1,8 @@
 use super::PtvError;
 use crate::data::{FromRead, FromReadVar, WriteTo, WriteVarTo};
+use crate::render::Sample;
 
+use std::f64::consts::TAU;
 use std::io::{Read, Seek, Write};
 
 //--------------------------------------------------------------------------------------------------
@@ -69,6 +71,87 @@ impl PtvWave {
     pub fn oscillator_from_pairs(harmonics: Box<[(i32, i32)]>) -> Self {
         Self::Oscillator { harmonics }
     }
+
+    /// Returns the normalized value of sample `index` when one period of this waveform is rendered
+    /// into `len` samples. Unit volume is not applied, so ±1.0 corresponds to full scale at a unit
+    /// volume of 128.
+    ///
+    /// Coordinate waveforms are linearly interpolated between points in the same way as pxtone,
+    /// including the segment wrapping from the last point back to the first at `x_width`. Each
+    /// oscillator harmonic contributes a sine wave with amplitude `amplitude / harmonic_num / 128`.
+    pub fn sample_at(&self, index: usize, len: usize) -> f32 {
+        match self {
+            Self::Coordinate { points, x_width } => {
+                let (Some(first), Some(last)) = (points.first(), points.last()) else {
+                    return 0.;
+                };
+                // Position along the x-axis, truncated like pxtone does.
+                let x = ((*x_width as i64) * (index as i64) / (len.max(1) as i64)) as i32;
+
+                // Find the pair of points surrounding `x`.
+                let next = points.iter().position(|&(px, _)| (px as i32) > x);
+                let ((x1, y1), (x2, y2)) = match next {
+                    // Past the last point, so wrap around towards the first.
+                    None => ((last.0 as i32, last.1), (*x_width, first.1)),
+                    // Before the first point, so hold its value.
+                    Some(0) => ((first.0 as i32, first.1), (first.0 as i32, first.1)),
+                    Some(i) => {
+                        let (px1, py1) = points[i - 1];
+                        let (px2, py2) = points[i];
+                        ((px1 as i32, py1), (px2 as i32, py2))
+                    }
+                };
+
+                let y = if (x == x1) || (x1 == x2) {
+                    y1 as f64
+                } else {
+                    (y1 as f64)
+                        + ((y2 as f64) - (y1 as f64)) * ((x - x1) as f64) / ((x2 - x1) as f64)
+                };
+
+                (y / 128.) as f32
+            }
+
+            Self::Oscillator { harmonics } => {
+                let phase = TAU * (index as f64) / (len.max(1) as f64);
+                let y = harmonics
+                    .iter()
+                    // Harmonic 0 is silent (and would otherwise divide by zero).
+                    .filter(|&&(harmonic_num, _)| harmonic_num != 0)
+                    .map(|&(harmonic_num, amplitude)| {
+                        let harmonic_num = harmonic_num as f64;
+                        (phase * harmonic_num).sin() * (amplitude as f64) / harmonic_num / 128.
+                    })
+                    .sum::<f64>();
+
+                y as f32
+            }
+        }
+    }
+
+    /// Renders one period of this waveform into `len` samples. Unit volume is not applied.
+    ///
+    /// ```
+    /// # use ptcrab::ptvoice::PtvWave;
+    /// // Points are linearly interpolated, wrapping from the last point back to the first.
+    /// let wave = PtvWave::coordinate_from_points(Box::new([(0, 64), (128, -64)]));
+    /// assert_eq!(*wave.render::<f32>(4), [0.5, 0., -0.5, 0.]);
+    /// assert_eq!(*wave.render::<i16>(4), [16383, 0, -16383, 0]);
+    /// ```
+    pub fn render<S: Sample>(&self, len: usize) -> Box<[S]> {
+        let mut buffer = vec![S::default(); len].into_boxed_slice();
+        self.render_into(&mut buffer);
+
+        buffer
+    }
+    /// Renders one period of this waveform into the given buffer, using its entire length as the
+    /// period. Unit volume is not applied.
+    pub fn render_into<S: Sample>(&self, buffer: &mut [S]) {
+        let len = buffer.len();
+        for (index, sample) in buffer.iter_mut().enumerate() {
+            *sample = S::from_f32(self.sample_at(index, len));
+        }
+    }
 }
 
 impl FromRead<Self> for PtvWave {
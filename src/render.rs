@@ -0,0 +1,11 @@
+//! Audio rendering functionality.
+
+mod effect;
+mod mixer;
+mod renderer;
+mod sample;
+pub(crate) mod tone;
+mod unit;
+pub use self::mixer::*;
+pub use self::renderer::*;
+pub use self::sample::*;
@@ -0,0 +1,88 @@
+use crate::project::{Delay, Master, Overdrive, Project};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Playback state of a delay effect.
+#[derive(Clone, Debug)]
+pub(crate) struct DelayState {
+    /// Index of the affected unit group.
+    pub group: usize,
+    /// Volume of the delayed signal as a percentage.
+    rate: i32,
+    /// Previous input for each stereo channel, which is empty if the delay has no effect.
+    buf: Box<[[i32; 2]]>,
+    /// Index of the oldest input in the buffer.
+    offset: usize,
+}
+
+impl DelayState {
+    pub fn new(delay: &Delay, master: &Master, sample_rate: u32) -> Self {
+        Self {
+            // Like pxtone, invalid groups are replaced with group 0.
+            group: Some(delay.group as usize)
+                .filter(|&group| group < Project::GROUP_COUNT)
+                .unwrap_or(0),
+            rate: delay.rate as i32,
+            buf: vec![[0, 0]; delay.sample_count(master, sample_rate)].into_boxed_slice(),
+            offset: 0,
+        }
+    }
+
+    /// Returns the length of the delay in samples.
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Silences the delay.
+    pub fn reset(&mut self) {
+        self.buf.fill([0, 0]);
+        self.offset = 0;
+    }
+
+    /// Mixes the delayed signal into `work` on channel `ch`. If `feed` is true, `work` is the
+    /// output of the delay's group and is fed back into the delay; otherwise only the delayed
+    /// signal is.
+    pub fn process(&mut self, ch: usize, work: &mut i32, feed: bool) {
+        let Some(frame) = self.buf.get_mut(self.offset) else {
+            return;
+        };
+        let echo = frame[ch] * self.rate / 100;
+        let input = if feed { *work } else { 0 };
+        *work += echo;
+        frame[ch] = input + echo;
+    }
+
+    /// Advances the delay by one sample.
+    pub fn increment(&mut self) {
+        if !self.buf.is_empty() {
+            self.offset = (self.offset + 1) % self.buf.len();
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Prepared overdrive effect.
+#[derive(Clone, Debug)]
+pub(crate) struct OverdriveState {
+    /// Index of the affected unit group, if valid.
+    pub group: Option<usize>,
+    /// Peak value left after clipping.
+    top: i32,
+    amp: f32,
+}
+
+impl OverdriveState {
+    pub fn new(overdrive: &Overdrive) -> Self {
+        Self {
+            group: Some(overdrive.group as usize).filter(|&group| group < Project::GROUP_COUNT),
+            top: overdrive.clip_level(),
+            amp: overdrive.amp,
+        }
+    }
+
+    /// Clips & amplifies `work`.
+    pub fn process(&self, work: &mut i32) {
+        *work = ((*work).clamp(-self.top, self.top) as f64 * self.amp as f64) as i32;
+    }
+}
@@ -0,0 +1,38 @@
+/// Mixer settings for a single unit, applied on top of the project's own volume & panning without
+/// modifying project data. These can be changed at any time during playback.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UnitMixer {
+    /// Silences the unit. Muted units are still played silently, so unmuting one resumes it right
+    /// where it would be.
+    pub muted: bool,
+    /// Silences every unit that isn't soloed, if any unit is soloed. Muted units stay silent even
+    /// if soloed.
+    pub soloed: bool,
+    /// Linear gain multiplier.
+    pub gain: f32,
+    /// Stereo balance from -1.0 (left only) to 1.0 (right only), where 0.0 leaves both channels
+    /// untouched.
+    pub pan: f32,
+}
+
+impl UnitMixer {
+    /// Returns the multiplier applied to each stereo channel, or [`None`] if the unit is silent.
+    pub(crate) fn channel_gains(&self, any_soloed: bool) -> Option<[f32; 2]> {
+        if self.muted || (any_soloed && !self.soloed) {
+            return None;
+        }
+        let pan = self.pan.clamp(-1., 1.);
+        Some([1. - pan.max(0.), 1. + pan.min(0.)].map(|balance| self.gain * balance))
+    }
+}
+
+impl Default for UnitMixer {
+    fn default() -> Self {
+        Self {
+            muted: false,
+            soloed: false,
+            gain: 1.,
+            pan: 0.,
+        }
+    }
+}
@@ -0,0 +1,828 @@
+use super::effect::{DelayState, OverdriveState};
+use super::tone::{self, VoiceInstance, BASE_SAMPLE_RATE};
+use super::unit::{UnitState, GROUP_COUNT, TIME_PAN_LEN};
+use super::{Sample, UnitMixer};
+use crate::project::{Event, EventKind, Note, Project, Timing};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Plays a project as interleaved stereo samples, following pxtone's own playback engine.
+///
+/// The renderer keeps its own copy of everything it needs from the project, so the project can be
+/// modified or dropped while rendering, and the renderer can be sent to another thread (such as an
+/// audio callback). All memory is allocated up front by [`Renderer::new`], so rendering itself
+/// never allocates.
+///
+/// ```
+/// # use ptcrab::project::{Event, EventKind, Project, Unit};
+/// # use ptcrab::ptvoice::{PtvUnit, Ptvoice};
+/// # use ptcrab::render::Renderer;
+/// let mut project = Project::default();
+/// project.voices = Box::new([Ptvoice::new(Box::new([PtvUnit::default()])).into()]);
+/// project.units = vec![Unit::default(); 1].into();
+/// project.events = Box::new([Event::new(0, 0, EventKind::On(480))]);
+///
+/// // One measure of 4 beats at 120 BPM lasts 2 seconds.
+/// let mut renderer = Renderer::new(&project, 44100);
+/// let mut buffer = vec![0_i16; 2 * 44100];
+/// assert_eq!(renderer.render(&mut buffer), 44100);
+/// assert_eq!(renderer.render(&mut buffer), 44100);
+/// assert_eq!(renderer.render(&mut buffer), 0);
+/// assert!(renderer.is_finished());
+/// ```
+///
+/// Notes can last until the end of the clock range, even when they start part-way through:
+///
+/// ```
+/// # use ptcrab::project::{Event, EventKind, Project, Unit};
+/// # use ptcrab::ptvoice::{PtvUnit, PtvWave, Ptvoice};
+/// # use ptcrab::render::Renderer;
+/// # let mut project = Project::default();
+/// # let mut unit = PtvUnit::default();
+/// # unit.wave = Some(PtvWave::oscillator_from_pairs(Box::new([(1, 128)])));
+/// # project.voices = Box::new([Ptvoice::new(Box::new([unit])).into()]);
+/// # project.units = vec![Unit::default(); 1].into();
+/// project.events = Box::new([Event::new(10, 0, EventKind::On(i32::MAX - 5))]);
+///
+/// let mut renderer = Renderer::new(&project, 44100);
+/// let mut buffer = vec![0_i16; 2 * 44100];
+/// assert_eq!(renderer.render(&mut buffer), 44100);
+/// assert!(buffer.iter().any(|&sample| sample != 0));
+/// ```
+///
+/// When looping indefinitely, playback never finishes:
+///
+/// ```
+/// # use ptcrab::project::{Event, EventKind, Project, Unit};
+/// # use ptcrab::ptvoice::{PtvUnit, Ptvoice};
+/// # use ptcrab::render::Renderer;
+/// # let mut project = Project::default();
+/// # project.voices = Box::new([Ptvoice::new(Box::new([PtvUnit::default()])).into()]);
+/// # project.units = vec![Unit::default(); 1].into();
+/// # project.events = Box::new([Event::new(0, 0, EventKind::On(480))]);
+/// let mut renderer = Renderer::new(&project, 48000);
+/// renderer.set_loop_count(None);
+///
+/// let audio_thread = std::thread::spawn(move || {
+///     let mut buffer = [0_f32; 2 * 512];
+///     for _ in 0..1000 {
+///         assert_eq!(renderer.render(&mut buffer), 512);
+///     }
+/// });
+/// audio_thread.join().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Renderer {
+    song: Song,
+    /// Mixer settings for each unit.
+    mixers: Box<[UnitMixer]>,
+    /// Whether group effects are skipped.
+    effects_bypassed: bool,
+    /// Kind of stems most recently rendered, which are replayed when seeking.
+    stem_kind: StemKind,
+    /// Number of times the repeat region is replayed, or [`None`] to loop indefinitely.
+    loop_count: Option<u32>,
+    /// Duration in seconds of the fade-out after the last loop, if any.
+    fade_out_secs: Option<f32>,
+
+    units: Box<[UnitState]>,
+    /// Index of the next event to be processed.
+    event_index: usize,
+    /// Number of samples rendered since the start of the project.
+    sample_index: i32,
+    /// Index into each unit's pan-time buffer.
+    time_pan_index: usize,
+    /// Output of each stem (or group, when mixing down) for the current frame.
+    buses: Box<[[i32; 2]]>,
+    /// Delays applied to groups.
+    delays: Box<[DelayState]>,
+    /// Delays applied to each unit separately when rendering stems by unit, ordered by unit and
+    /// then by delay. These are only allocated once needed.
+    unit_delays: Box<[DelayState]>,
+    /// Number of times the repeat region has been replayed so far.
+    loops_played: u32,
+    /// Fade-out in progress, if any.
+    fade: Option<Fade>,
+    finished: bool,
+}
+
+/// Way of splitting a project's output into separate stereo stems.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StemKind {
+    /// One stem per unit.
+    Unit,
+    /// One stem per group, as used by effects.
+    Group,
+}
+
+/// Progress of a fade-out, following pxtone's fixed-point fade volume.
+#[derive(Clone, Copy, Debug)]
+struct Fade {
+    /// Remaining frames until silence.
+    count: i32,
+    /// Fade duration in frames, divided by 256.
+    max: i32,
+}
+
+/// Project data prepared for playback, which stays constant while rendering.
+#[derive(Clone, Debug)]
+struct Song {
+    timing: Timing,
+    smooth_len: i32,
+    /// Sample at which playback ends.
+    end_sample: i32,
+    /// Sample at which playback restarts when looping.
+    repeat_sample: i32,
+
+    /// Events in playback order, excluding those for nonexistent units.
+    events: Box<[Event]>,
+    /// Voice-units of each project voice.
+    voices: Box<[Box<[VoiceInstance]>]>,
+    /// Initial state of each delay.
+    delays: Box<[DelayState]>,
+    overdrives: Box<[OverdriveState]>,
+}
+
+impl Renderer {
+    /// Prepares a project for playback at the given sample rate.
+    pub fn new(project: &Project, sample_rate: u32) -> Self {
+        let master = &project.master;
+        let timing = master.timing(sample_rate);
+        let loop_range = project.loop_range();
+        let [repeat_sample, end_sample] =
+            [loop_range.start, loop_range.end].map(|clock| timing.clock_to_frame(clock));
+
+        let mut events: Box<[_]> = project
+            .events
+            .iter()
+            .filter(|event| (event.unit as usize) < project.units.len())
+            .cloned()
+            .collect();
+        events.sort_by(Event::cmp_order);
+
+        let voices: Box<[_]> = project
+            .voices
+            .iter()
+            .map(|voice| VoiceInstance::from_voice(&voice.voice, sample_rate))
+            .collect();
+        let max_tones = voices.iter().map(|units| units.len()).max().unwrap_or(0);
+
+        let song = Song {
+            timing,
+            smooth_len: tone::smooth_len(sample_rate),
+            end_sample,
+            repeat_sample,
+
+            events,
+            voices,
+            delays: project
+                .delays
+                .iter()
+                .map(|delay| DelayState::new(delay, master, sample_rate))
+                .collect(),
+            overdrives: project.overdrives.iter().map(OverdriveState::new).collect(),
+        };
+        let units: Box<[_]> = (0..project.units.len())
+            .map(|_| UnitState::new(max_tones))
+            .collect();
+        let bus_count = units.len().max(GROUP_COUNT);
+
+        let mut renderer = Self {
+            mixers: vec![UnitMixer::default(); units.len()].into_boxed_slice(),
+            effects_bypassed: false,
+            stem_kind: StemKind::Group,
+            loop_count: Some(0),
+            fade_out_secs: None,
+
+            units,
+            event_index: 0,
+            sample_index: 0,
+            time_pan_index: 0,
+            buses: vec![[0, 0]; bus_count].into_boxed_slice(),
+            delays: song.delays.clone(),
+            unit_delays: Box::new([]),
+            loops_played: 0,
+            fade: None,
+            finished: false,
+            song,
+        };
+        renderer.init_units();
+        renderer
+    }
+
+    /// Returns the output sample rate.
+    pub fn sample_rate(&self) -> u32 {
+        self.song.timing.sample_rate()
+    }
+    /// Returns conversions between clocks & frames at the output sample rate.
+    pub fn timing(&self) -> &Timing {
+        &self.song.timing
+    }
+    /// Returns the number of units in the project.
+    pub fn unit_count(&self) -> usize {
+        self.units.len()
+    }
+    /// Returns the number of stems that the project's output is split into. See
+    /// [`Self::render_stems`].
+    pub fn stem_count(&self, kind: StemKind) -> usize {
+        match kind {
+            StemKind::Unit => self.units.len(),
+            StemKind::Group => GROUP_COUNT,
+        }
+    }
+    /// Returns the number of stereo frames in a single pass through the project.
+    pub fn frame_count(&self) -> usize {
+        self.song.end_sample.max(0) as usize
+    }
+    /// Returns the total number of stereo frames rendered from the start of the project until
+    /// playback finishes, including loops & fade-out, or [`None`] if looping indefinitely.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Project, Unit};
+    /// # use ptcrab::ptvoice::{PtvUnit, Ptvoice};
+    /// # use ptcrab::render::Renderer;
+    /// # let mut project = Project::default();
+    /// # project.voices = Box::new([Ptvoice::new(Box::new([PtvUnit::default()])).into()]);
+    /// # project.units = vec![Unit::default(); 1].into();
+    /// project.events = Box::new([Event::new(0, 0, EventKind::On(1920 * 2))]);
+    /// project.master.set_repeat_measure(1);
+    ///
+    /// // Play the intro & repeat region, loop twice, then fade out over one second.
+    /// let mut renderer = Renderer::new(&project, 44100);
+    /// renderer.set_loop_count(Some(2));
+    /// renderer.set_fade_out(Some(1.));
+    /// let frame_count = renderer.total_frame_count().unwrap();
+    /// assert_eq!(frame_count, (4 + 2 + 2) * 44100 + 44032);
+    ///
+    /// let mut buffer = vec![0_f32; 2 * frame_count];
+    /// assert_eq!(renderer.render(&mut buffer), frame_count);
+    /// assert!(renderer.is_finished());
+    /// ```
+    pub fn total_frame_count(&self) -> Option<usize> {
+        let loop_count = self.loop_count? as usize;
+        let fade_len = match self.fade_out_secs {
+            Some(secs) => Self::fade_max(self.song.timing.sample_rate(), secs) << 8,
+            None => 0,
+        };
+        let repeat_len = (self.song.end_sample - self.song.repeat_sample).max(0) as usize;
+
+        Some(self.frame_count() + (loop_count * repeat_len) + (fade_len as usize))
+    }
+    /// Returns whether playback has finished. This is never the case while looping indefinitely.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the number of times the repeat region is replayed after the first pass through
+    /// the project, or [`None`] if looping indefinitely.
+    pub fn loop_count(&self) -> Option<u32> {
+        self.loop_count
+    }
+    /// Sets the number of times the repeat region is replayed after the first pass through the
+    /// project, or [`None`] to loop indefinitely. Defaults to 0, which plays the project once.
+    pub fn set_loop_count(&mut self, loop_count: Option<u32>) {
+        self.loop_count = loop_count;
+    }
+
+    /// Returns the duration in seconds of the fade-out after the last loop, if any.
+    pub fn fade_out_secs(&self) -> Option<f32> {
+        self.fade_out_secs
+    }
+    /// Sets the duration in seconds of the fade-out after the last loop, or [`None`] to play
+    /// through to the end of the project instead. While fading out, the repeat region continues
+    /// to loop.
+    pub fn set_fade_out(&mut self, secs: Option<f32>) {
+        self.fade_out_secs = secs;
+    }
+    /// Starts fading out immediately over the given duration in seconds, after which playback
+    /// finishes. Has no effect if already fading out.
+    pub fn fade_out(&mut self, secs: f32) {
+        if self.fade.is_none() {
+            let max = Self::fade_max(self.song.timing.sample_rate(), secs);
+            self.fade = Some(Fade {
+                count: max << 8,
+                max,
+            });
+            if max <= 0 {
+                self.finished = true;
+            }
+        }
+    }
+
+    /// Returns the mixer settings of each unit.
+    pub fn mixers(&self) -> &[UnitMixer] {
+        &self.mixers
+    }
+    /// Returns the mixer settings of each unit for modification, which takes effect from the next
+    /// frame rendered.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Project, Unit};
+    /// # use ptcrab::ptvoice::{PtvUnit, PtvWave, Ptvoice};
+    /// # use ptcrab::render::Renderer;
+    /// # let mut project = Project::default();
+    /// # let mut unit = PtvUnit::default();
+    /// # unit.wave = Some(PtvWave::oscillator_from_pairs(Box::new([(1, 128)])));
+    /// # project.voices = Box::new([Ptvoice::new(Box::new([unit])).into()]);
+    /// project.units = vec![Unit::default(); 2].into();
+    /// project.events = Box::new([
+    ///     Event::new(0, 0, EventKind::On(1920)),
+    ///     Event::new(0, 1, EventKind::On(1920)),
+    /// ]);
+    ///
+    /// let mut renderer = Renderer::new(&project, 44100);
+    /// let mut both = vec![0_i16; 2 * 1000];
+    /// renderer.render(&mut both);
+    ///
+    /// // Solo unit 0 & pan it hard left partway through playback.
+    /// renderer.mixers_mut()[0].soloed = true;
+    /// renderer.mixers_mut()[0].pan = -1.;
+    /// let mut solo = vec![0_i16; 2 * 1000];
+    /// renderer.render(&mut solo);
+    /// assert!(solo.chunks(2).all(|frame| frame[1] == 0));
+    /// assert!(solo.chunks(2).any(|frame| frame[0] != 0));
+    /// ```
+    pub fn mixers_mut(&mut self) -> &mut [UnitMixer] {
+        &mut self.mixers
+    }
+
+    /// Returns whether group effects are skipped.
+    pub fn is_effects_bypassed(&self) -> bool {
+        self.effects_bypassed
+    }
+    /// Sets whether group effects are skipped, silencing any delays. When rendering stems by unit,
+    /// effects are applied to each stem separately.
+    pub fn set_effects_bypassed(&mut self, bypassed: bool) {
+        self.effects_bypassed = bypassed;
+        self.reset_effects();
+    }
+
+    /// Returns the current playback position in stereo frames from the start of the project.
+    pub fn position(&self) -> usize {
+        self.sample_index.max(0) as usize
+    }
+
+    /// Jumps to the given position in stereo frames from the start of the project, as if it were
+    /// the first pass through the project. Positions past the end of the project are clamped to
+    /// the end.
+    ///
+    /// Playback continues exactly as if the project had been rendered from the start, including
+    /// any notes that are already sounding. This means seeking takes longer the further into the
+    /// project the position is, although audio is only mixed for the last few frames before it.
+    /// Audio is mixed into the kind of stems most recently rendered, so effects carry over to
+    /// [`Self::render_stems`] as well.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Event, EventKind, Project, Unit};
+    /// # use ptcrab::ptvoice::{PtvUnit, PtvWave, Ptvoice};
+    /// # use ptcrab::render::Renderer;
+    /// # let mut project = Project::default();
+    /// # let mut unit = PtvUnit::default();
+    /// # unit.wave = Some(PtvWave::oscillator_from_pairs(Box::new([(1, 128)])));
+    /// # project.voices = Box::new([Ptvoice::new(Box::new([unit])).into()]);
+    /// # project.units = vec![Unit::default(); 1].into();
+    /// # project.events = Box::new([Event::new(0, 0, EventKind::On(960))]);
+    /// let mut renderer = Renderer::new(&project, 44100);
+    /// let mut from_start = vec![0_i16; 2 * 44100];
+    /// renderer.render(&mut from_start);
+    ///
+    /// renderer.seek(10000);
+    /// let mut from_seek = vec![0_i16; 2 * (44100 - 10000)];
+    /// renderer.render(&mut from_seek);
+    /// assert_eq!(from_seek, from_start[(2 * 10000)..]);
+    ///
+    /// renderer.seek_to_measure(2_000_000);
+    /// assert_eq!(renderer.position(), renderer.frame_count());
+    /// ```
+    pub fn seek(&mut self, frame: usize) {
+        let target = frame.min(self.frame_count()) as i32;
+        let mix_start = target.saturating_sub(self.history_len().try_into().unwrap_or(i32::MAX));
+
+        self.sample_index = 0;
+        self.event_index = 0;
+        self.time_pan_index = 0;
+        self.loops_played = 0;
+        self.fade = None;
+        self.finished = false;
+        for unit in self.units.iter_mut() {
+            unit.reset();
+        }
+        self.init_units();
+        self.reset_effects();
+
+        while self.sample_index < target {
+            let mix = self.sample_index >= mix_start;
+            self.process_frame(mix.then_some(self.stem_kind));
+            self.advance();
+        }
+        self.check_loop();
+    }
+    /// Jumps to the given clock. See [`Self::seek`].
+    pub fn seek_to_clock(&mut self, clock: i32) {
+        let frame = self.song.timing.clock_to_frame(clock.max(0));
+        self.seek(frame.max(0) as usize);
+    }
+    /// Jumps to the start of the given measure. See [`Self::seek`].
+    pub fn seek_to_measure(&mut self, measure: i32) {
+        self.seek_to_clock(self.song.timing.measure_clock().saturating_mul(measure));
+    }
+
+    /// Renders interleaved stereo samples into `buffer` until it's full or the end of the project
+    /// is reached, returning the number of stereo frames rendered. While looping indefinitely, the
+    /// buffer is always filled.
+    pub fn render<S: Sample>(&mut self, buffer: &mut [S]) -> usize {
+        self.stem_kind = StemKind::Group;
+        let mut frame_count = 0;
+        for frame in buffer.chunks_exact_mut(2) {
+            if self.finished {
+                break;
+            }
+            self.process_frame(Some(StemKind::Group));
+            for ch in 0..2 {
+                let work = self.buses[..GROUP_COUNT].iter().map(|bus| bus[ch]).sum();
+                frame[ch] = S::from_i16(self.output(work));
+            }
+            self.advance();
+            frame_count += 1;
+        }
+        frame_count
+    }
+
+    /// Renders interleaved stereo samples for each stem into the matching buffer in `stems`, until
+    /// the shortest buffer is full or the end of the project is reached, returning the number of
+    /// stereo frames rendered. Each unit's mixer settings apply to its stem and its group's stem.
+    ///
+    /// Each stem is clamped separately, so adding stems together may not exactly reproduce
+    /// [`Self::render`]'s output if it clips.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of buffers in `stems` doesn't match [`Self::stem_count`].
+    ///
+    /// # Allocation
+    ///
+    /// When rendering stems by unit with effects applied, the first call allocates separate effect
+    /// state for each unit.
+    ///
+    /// ```
+    /// # use ptcrab::project::{Delay, DelayUnit, Event, EventKind, Project, Unit};
+    /// # use ptcrab::ptvoice::{PtvUnit, PtvWave, Ptvoice};
+    /// # use ptcrab::render::{Renderer, StemKind};
+    /// let mut project = Project::default();
+    /// let mut unit = PtvUnit::default();
+    /// unit.wave = Some(PtvWave::oscillator_from_pairs(Box::new([(1, 128)])));
+    /// project.voices = Box::new([Ptvoice::new(Box::new([unit])).into()]);
+    /// project.units = vec![Unit::default(); 2].into();
+    /// project.events = Box::new([
+    ///     Event::new(0, 0, EventKind::On(480)),
+    ///     Event::new(0, 1, EventKind::On(480)),
+    /// ]);
+    ///
+    /// let mut renderer = Renderer::new(&project, 44100);
+    /// renderer.mixers_mut()[1].muted = true;
+    /// let mut stems = vec![vec![0_i16; 2 * 1000]; renderer.stem_count(StemKind::Unit)];
+    /// let mut buffers: Vec<_> = stems.iter_mut().map(|stem| &mut stem[..]).collect();
+    /// assert_eq!(renderer.render_stems(StemKind::Unit, &mut buffers), 1000);
+    ///
+    /// // Unit 0 plays alone in its stem, while muted unit 1 is silent.
+    /// let mut mixdown = vec![0_i16; 2 * 1000];
+    /// renderer.seek(0);
+    /// renderer.render(&mut mixdown);
+    /// assert!(stems[0].iter().any(|&sample| sample != 0));
+    /// assert_eq!(stems[0], mixdown);
+    /// assert!(stems[1].iter().all(|&sample| sample == 0));
+    ///
+    /// // Seeking replays unit stems, including their delays.
+    /// project.delays = Box::new([Delay::new(DelayUnit::Second, 0, 50., 40.)]);
+    /// let mut renderer = Renderer::new(&project, 44100);
+    /// let render_from = |renderer: &mut Renderer, frame| {
+    ///     let mut stems = vec![vec![0_i16; 2 * 2000]; 2];
+    ///     let mut buffers: Vec<_> = stems.iter_mut().map(|stem| &mut stem[..]).collect();
+    ///     renderer.seek(frame);
+    ///     renderer.render_stems(StemKind::Unit, &mut buffers);
+    ///     stems
+    /// };
+    /// let from_start = render_from(&mut renderer, 0);
+    /// let from_seek = render_from(&mut renderer, 1500);
+    /// assert_eq!(from_seek[0][..1000], from_start[0][3000..]);
+    /// ```
+    pub fn render_stems<S: Sample>(&mut self, kind: StemKind, stems: &mut [&mut [S]]) -> usize {
+        assert_eq!(stems.len(), self.stem_count(kind), "wrong number of stems");
+        self.stem_kind = kind;
+        if (kind == StemKind::Unit) && self.unit_delays.is_empty() {
+            self.unit_delays = self
+                .units
+                .iter()
+                .flat_map(|_| self.song.delays.iter().cloned())
+                .collect();
+        }
+
+        let len = stems.iter().map(|stem| stem.len() / 2).min().unwrap_or(0);
+        let mut frame_count = 0;
+        while frame_count < len {
+            if self.finished {
+                break;
+            }
+            self.process_frame(Some(kind));
+            for (stem, bus) in stems.iter_mut().zip(self.buses.iter()) {
+                for ch in 0..2 {
+                    stem[(2 * frame_count) + ch] = S::from_i16(self.output(bus[ch]));
+                }
+            }
+            self.advance();
+            frame_count += 1;
+        }
+        frame_count
+    }
+
+    /// Resets every unit to its default state, playing the first voice.
+    fn init_units(&mut self) {
+        let song = &self.song;
+        for unit in self.units.iter_mut() {
+            unit.init();
+            song.set_voice(unit, Note::DEFAULT_VOICE);
+        }
+    }
+
+    /// Returns the fade-out duration in frames, divided by 256.
+    fn fade_max(sample_rate: u32, secs: f32) -> i32 {
+        ((sample_rate as f32) * secs) as i32 >> 8
+    }
+
+    /// Silences every effect.
+    fn reset_effects(&mut self) {
+        for delay in self.delays.iter_mut().chain(self.unit_delays.iter_mut()) {
+            delay.reset();
+        }
+    }
+
+    /// Returns the number of frames before the current one that affect its output.
+    fn history_len(&self) -> usize {
+        // Delays feed back into themselves, so their output depends on every previous frame.
+        let has_delays = self.delays.iter().any(|delay| delay.len() > 0);
+        if has_delays && !self.effects_bypassed {
+            usize::MAX
+        } else {
+            TIME_PAN_LEN
+        }
+    }
+
+    /// Applies the fade-out (if any) to a mixed value, clamping it to 16 bits.
+    fn output(&self, mut work: i32) -> i16 {
+        if let Some(fade) = &self.fade {
+            // Scaled in 64 bits, since loud mixes can overflow when multiplied by long fades.
+            let scaled = (work as i64) * ((fade.count >> 8) as i64) / (fade.max as i64);
+            work = scaled as i32;
+        }
+        work.clamp(-32767, 32767) as i16
+    }
+
+    /// Processes events for the current frame and mixes each unit's output into buses, split into
+    /// the given kind of stems. If `mix` is [`None`], the frame isn't mixed, but the state of each
+    /// unit is still updated.
+    fn process_frame(&mut self, mix: Option<StemKind>) {
+        let song = &self.song;
+        for unit in self.units.iter_mut() {
+            unit.update_envelope(&song.voices);
+        }
+
+        // Process events up to the current clock.
+        let clock = song.timing.frame_to_clock(self.sample_index);
+        while let Some(event) = song.events.get(self.event_index) {
+            if event.clock > clock {
+                break;
+            }
+            song.process_event(&mut self.units, self.event_index, clock);
+            self.event_index += 1;
+        }
+
+        let Some(kind) = mix else {
+            return;
+        };
+        for unit in self.units.iter_mut() {
+            unit.sample(&song.voices, self.time_pan_index, song.smooth_len);
+        }
+        self.buses.fill([0, 0]);
+        let any_soloed = self.mixers.iter().any(|mixer| mixer.soloed);
+        for (index, (unit, mixer)) in self.units.iter().zip(self.mixers.iter()).enumerate() {
+            let Some(gains) = mixer.channel_gains(any_soloed) else {
+                continue;
+            };
+            let bus = match kind {
+                StemKind::Unit => &mut self.buses[index],
+                StemKind::Group => &mut self.buses[unit.group],
+            };
+            for (ch, (work, gain)) in bus.iter_mut().zip(gains).enumerate() {
+                *work += ((unit.output(ch, self.time_pan_index) as f32) * gain) as i32;
+            }
+        }
+
+        if !self.effects_bypassed {
+            self.apply_effects(kind);
+        }
+    }
+
+    /// Applies group effects to each bus, split into the given kind of stems.
+    fn apply_effects(&mut self, kind: StemKind) {
+        // Like pxtone, overdrives are applied before delays.
+        for overdrive in self.song.overdrives.iter() {
+            let Some(group) = overdrive.group else {
+                continue;
+            };
+            match kind {
+                StemKind::Group => {
+                    self.buses[group]
+                        .iter_mut()
+                        .for_each(|work| overdrive.process(work));
+                }
+                StemKind::Unit => {
+                    for (unit, bus) in self.units.iter().zip(self.buses.iter_mut()) {
+                        if unit.group == group {
+                            bus.iter_mut().for_each(|work| overdrive.process(work));
+                        }
+                    }
+                }
+            }
+        }
+
+        match kind {
+            StemKind::Group => {
+                for delay in self.delays.iter_mut() {
+                    for ch in 0..2 {
+                        delay.process(ch, &mut self.buses[delay.group][ch], true);
+                    }
+                }
+            }
+            StemKind::Unit if !self.delays.is_empty() => {
+                // Each unit's delays only take input while the unit is in the affected group, but
+                // echoes continue after it leaves.
+                let unit_delays = self.unit_delays.chunks_exact_mut(self.delays.len());
+                for ((unit, bus), delays) in self
+                    .units
+                    .iter()
+                    .zip(self.buses.iter_mut())
+                    .zip(unit_delays)
+                {
+                    for delay in delays.iter_mut() {
+                        let feed = delay.group == unit.group;
+                        for (ch, work) in bus.iter_mut().enumerate() {
+                            delay.process(ch, work, feed);
+                        }
+                    }
+                }
+            }
+            StemKind::Unit => {}
+        }
+    }
+
+    /// Advances playback to the next frame.
+    fn advance(&mut self) {
+        let song = &self.song;
+        self.sample_index += 1;
+        self.time_pan_index = (self.time_pan_index + 1) % TIME_PAN_LEN;
+        let stride = (BASE_SAMPLE_RATE as f32) / (song.timing.sample_rate() as f32);
+        for unit in self.units.iter_mut() {
+            let key = unit.increment_key();
+            unit.increment_sample(&song.voices, tone::table_key_freq(key) * stride);
+        }
+        for delay in self.delays.iter_mut().chain(self.unit_delays.iter_mut()) {
+            delay.increment();
+        }
+
+        if let Some(fade) = &mut self.fade {
+            fade.count -= 1;
+            if fade.count <= 0 {
+                self.finished = true;
+            }
+        }
+
+        self.check_loop();
+    }
+
+    /// Handles reaching the end of the project, either looping back to the repeat point or
+    /// finishing playback.
+    fn check_loop(&mut self) {
+        if self.sample_index < self.song.end_sample {
+            return;
+        }
+
+        if self
+            .loop_count
+            .is_none_or(|count| self.loops_played < count)
+        {
+            self.loops_played = self.loops_played.saturating_add(1);
+        } else if let Some(secs) = self.fade_out_secs.filter(|_| self.fade.is_none()) {
+            // Keep looping while fading out.
+            self.fade_out(secs);
+        } else if self.fade.is_none() {
+            self.finished = true;
+            return;
+        }
+
+        // Like pxtone, looping cuts off any sounding notes and replays events from the start to
+        // restore each unit's state.
+        self.sample_index = self.song.repeat_sample;
+        self.event_index = 0;
+        self.init_units();
+    }
+}
+
+impl Song {
+    /// Switches a unit to the voice at the given index, if it exists.
+    fn set_voice(&self, unit: &mut UnitState, index: usize) {
+        if let Some(instances) = self.voices.get(index) {
+            unit.set_voice(index, instances, self.timing.tempo());
+        }
+    }
+
+    /// Applies the event at the given index to its unit, where `clock` is the current clock.
+    fn process_event(&self, units: &mut [UnitState], index: usize, clock: i32) {
+        let event = &self.events[index];
+        let unit = &mut units[event.unit as usize];
+        let to_samples = |clocks: i32| self.timing.clock_len_to_frames(clocks);
+
+        match event.kind {
+            EventKind::On(len) => {
+                // Time remaining until note-off.
+                let remaining_len = len.saturating_sub(clock - event.clock);
+                let on_len = to_samples(remaining_len);
+                if on_len <= 0 {
+                    unit.zero_lives();
+                    return;
+                }
+                unit.key_on();
+
+                for (tone, instance) in unit.tones_mut(&self.voices) {
+                    let life = if instance.release_len > 0 {
+                        // The release is cut off by the unit's next note, or the end of the song.
+                        let release_clock = self.timing.frame_to_clock(instance.release_len);
+                        let release_end = event
+                            .clock
+                            .saturating_add(len)
+                            .saturating_add(release_clock);
+                        let next_on = self.events[(index + 1)..]
+                            .iter()
+                            .take_while(|next| next.clock <= release_end)
+                            .find(|next| {
+                                (next.unit == event.unit) && matches!(next.kind, EventKind::On(_))
+                            });
+                        let max_life = match next_on {
+                            Some(next) => to_samples(next.clock.saturating_sub(clock)),
+                            None => self.end_sample - to_samples(clock),
+                        };
+                        on_len.saturating_add(instance.release_len).min(max_life)
+                    } else {
+                        on_len
+                    };
+                    tone.start(instance, on_len, life);
+                }
+            }
+            EventKind::Key(key) => unit.set_key(key.as_value()),
+            EventKind::PanVolume(pan) => unit.set_pan_volume(pan.as_value()),
+            EventKind::Velocity(velocity) => unit.velocity = velocity.as_value(),
+            EventKind::Volume(volume) => unit.volume = volume.as_value(),
+            EventKind::Portamento(len) => unit.set_portamento(to_samples(len)),
+            EventKind::Voice(voice) => {
+                if let Ok(voice) = usize::try_from(voice) {
+                    self.set_voice(unit, voice);
+                }
+            }
+            EventKind::Group(group) => {
+                if let Some(group) = usize::try_from(group).ok().filter(|&g| g < GROUP_COUNT) {
+                    unit.group = group;
+                }
+            }
+            EventKind::Tuning(tuning) => unit.tuning = tuning.into(),
+            EventKind::PanTime(pan) => unit.set_pan_time(pan, self.timing.sample_rate()),
+            EventKind::Other { .. } => {}
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+impl Project {
+    /// Renders the whole project once as interleaved stereo samples at the given sample rate.
+    ///
+    /// See [`Renderer`] to render a project incrementally.
+    pub fn render<S: Sample>(&self, sample_rate: u32) -> Box<[S]> {
+        let mut renderer = Renderer::new(self, sample_rate);
+        let mut samples = vec![S::default(); 2 * renderer.frame_count()].into_boxed_slice();
+        renderer.render(&mut samples);
+
+        samples
+    }
+    /// Renders the whole project once as separate stems of interleaved stereo samples at the given
+    /// sample rate.
+    ///
+    /// See [`Renderer::render_stems`] to render stems incrementally.
+    pub fn render_stems<S: Sample>(&self, kind: StemKind, sample_rate: u32) -> Box<[Box<[S]>]> {
+        let mut renderer = Renderer::new(self, sample_rate);
+        let len = 2 * renderer.frame_count();
+        let mut stems = vec![vec![S::default(); len]; renderer.stem_count(kind)];
+        let mut buffers: Box<[_]> = stems.iter_mut().map(|stem| &mut stem[..]).collect();
+        renderer.render_stems(kind, &mut buffers);
+
+        stems.into_iter().map(Vec::into_boxed_slice).collect()
+    }
+}
@@ -0,0 +1,48 @@
+/// PCM sample format that rendered audio can be written as.
+///
+/// Rendering is performed using normalized values, where ±1.0 represents full scale.
+pub trait Sample: Copy + Default {
+    /// Converts from a normalized value. Integer formats clamp out-of-range values, as pxtone does.
+    fn from_f32(value: f32) -> Self;
+    /// Converts from a 16-bit sample, the format pxtone mixes in.
+    fn from_i16(value: i16) -> Self;
+    /// Converts to a normalized value.
+    fn to_f32(self) -> f32;
+}
+
+impl Sample for f32 {
+    #[inline]
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+    #[inline]
+    fn from_i16(value: i16) -> Self {
+        (value as f32) / 32767.
+    }
+    #[inline]
+    fn to_f32(self) -> f32 {
+        self
+    }
+}
+
+impl Sample for i16 {
+    /// Converts from a normalized value, clamping to ±32767 like pxtone's mixer.
+    ///
+    /// ```
+    /// # use ptcrab::render::Sample;
+    /// assert_eq!(i16::from_f32(1.),  32767);
+    /// assert_eq!(i16::from_f32(-2.), -32767);
+    /// ```
+    #[inline]
+    fn from_f32(value: f32) -> Self {
+        (value * 32767.).clamp(-32767., 32767.) as i16
+    }
+    #[inline]
+    fn from_i16(value: i16) -> Self {
+        value
+    }
+    #[inline]
+    fn to_f32(self) -> f32 {
+        (self as f32) / 32767.
+    }
+}
@@ -0,0 +1,344 @@
+use super::Sample;
+use crate::ptvoice::PtvUnit;
+use crate::voice::{Voice, VoiceFlags};
+use crate::{Key, Tuning, Volume};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Sample rate that voice sample tables are pitched relative to.
+pub(crate) const BASE_SAMPLE_RATE: u32 = 44100;
+/// Length of a ptvoice waveform's sample table.
+const PTV_TABLE_LEN: usize = 400;
+
+/// Single voice-unit prepared for playback at a particular sample rate.
+#[derive(Clone, Debug)]
+pub(crate) struct VoiceInstance {
+    /// Stereo sample table, pitched relative to [`BASE_SAMPLE_RATE`]. Unit volume & panning are
+    /// already applied.
+    pub samples: Box<[[i16; 2]]>,
+    /// Attack volumes (out of 128) for each output sample. Empty if the unit has no envelope.
+    pub envelope: Box<[u8]>,
+    /// Release duration in output samples.
+    pub release_len: i32,
+
+    pub flags: VoiceFlags,
+    pub inherent_key: Key,
+    pub tuning: Tuning,
+}
+
+impl VoiceInstance {
+    /// Prepares a ptvoice unit for playback at the given sample rate.
+    pub fn from_ptv_unit(unit: &PtvUnit, sample_rate: u32) -> Self {
+        let samples: Box<[_]> = match &unit.wave {
+            Some(wave) => {
+                let volume = unit.volume.as_ratio();
+                let (left, right) = unit.pan_volume.as_separate();
+                let pan = [left as f32 / 64., right as f32 / 64.];
+
+                (0..PTV_TABLE_LEN)
+                    .map(|index| {
+                        let value = wave.sample_at(index, PTV_TABLE_LEN) * volume * 32767.;
+                        pan.map(|pan| (value * pan).clamp(-32767., 32767.) as i16)
+                    })
+                    .collect()
+            }
+            None => Box::new([]),
+        };
+        let (envelope, release_len) = match &unit.envelope {
+            Some(envelope) => (
+                envelope.attack_table(sample_rate),
+                envelope.release_len(sample_rate) as i32,
+            ),
+            None => (Box::new([]) as Box<[_]>, 0),
+        };
+
+        Self {
+            samples,
+            envelope,
+            release_len,
+
+            flags: unit.flags.clone(),
+            inherent_key: unit.inherent_key,
+            tuning: unit.tuning,
+        }
+    }
+
+    /// Prepares a sampled voice for playback, given its stereo frames at [`BASE_SAMPLE_RATE`].
+    pub fn from_frames(
+        samples: Box<[[i16; 2]]>,
+        flags: VoiceFlags,
+        inherent_key: Key,
+        tuning: Tuning,
+    ) -> Self {
+        Self {
+            samples,
+            envelope: Box::new([]),
+            release_len: 0,
+
+            flags,
+            inherent_key,
+            tuning,
+        }
+    }
+
+    /// Prepares every voice-unit of a project voice for playback at the given sample rate. Voices
+    /// that can't be decoded are prepared as silence.
+    pub fn from_voice(voice: &Voice, sample_rate: u32) -> Box<[Self]> {
+        match voice {
+            Voice::Ptv(ptv) => ptv
+                .units
+                .iter()
+                .map(|unit| Self::from_ptv_unit(unit, sample_rate))
+                .collect(),
+            Voice::Ptn(ptn) => {
+                let samples = ptn.ptnoise.render::<i16>(BASE_SAMPLE_RATE, 2);
+                let frames = samples
+                    .chunks_exact(2)
+                    .map(|frame| [frame[0], frame[1]])
+                    .collect();
+                Box::new([Self::from_frames(
+                    frames,
+                    ptn.flags.clone(),
+                    ptn.inherent_key,
+                    ptn.tuning,
+                )])
+            }
+            Voice::Pcm(pcm) => Box::new([Self::from_frames(
+                pcm.to_stereo_frames(BASE_SAMPLE_RATE).unwrap_or_default(),
+                pcm.flags.clone(),
+                pcm.inherent_key,
+                pcm.tuning,
+            )]),
+            Voice::Ogg(ogg) => {
+                #[cfg(feature = "ogg")]
+                let frames = ogg.to_stereo_frames(BASE_SAMPLE_RATE).unwrap_or_default();
+                #[cfg(not(feature = "ogg"))]
+                let frames = Box::new([]);
+
+                Box::new([Self::from_frames(
+                    frames,
+                    ogg.flags.clone(),
+                    ogg.inherent_key,
+                    ogg.tuning,
+                )])
+            }
+        }
+    }
+
+    /// Returns the sample table step per [`BASE_SAMPLE_RATE`] sample at the default key, given the
+    /// current tempo in beats per minute.
+    pub fn offset_freq(&self, tempo: f32) -> f32 {
+        if self.flags.beat_fit {
+            // Stretch the sample table to last exactly one beat.
+            (self.samples.len() as f32 * tempo)
+                / (BASE_SAMPLE_RATE as f32 * 60. * f32::from(self.tuning))
+        } else {
+            key_freq(self.inherent_key.as_a4_offset()) * f32::from(self.tuning)
+        }
+    }
+}
+
+/// Returns the frequency ratio of a key offset, such that an offset of 12 semitones doubles
+/// frequency.
+#[inline]
+pub(crate) fn key_freq(key_offset: i32) -> f32 {
+    ((key_offset as f32) / (12. * 256.)).exp2()
+}
+
+/// Returns the frequency ratio of a key relative to A<sub>4</sub>, quantized to 1/16th of a semitone
+/// and limited to ±8 octaves like pxtone's frequency table.
+#[inline]
+pub(crate) fn table_key_freq(key: i32) -> f32 {
+    const TABLE_HALF_LEN: i32 = 8 * 12 * 16;
+
+    let index = ((key - Key::A4.as_value()) >> 4).clamp(-TABLE_HALF_LEN, TABLE_HALF_LEN - 1);
+    key_freq(index << 4)
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Playback state of a single voice-unit.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Tone {
+    /// Remaining samples until the tone is silenced, including its release.
+    life: i32,
+    /// Remaining samples until note-off.
+    on: i32,
+    /// Position within the sample table.
+    pos: f64,
+    /// Position within the attack or release portion of the envelope.
+    env_pos: i32,
+    /// Current envelope volume out of 128.
+    env_volume: i32,
+    /// Envelope volume at the start of the release.
+    env_start: i32,
+    /// Sample table step at the default key.
+    offset_freq: f32,
+}
+
+impl Tone {
+    /// Silences the tone and sets its step at the default key.
+    pub fn reset(&mut self, offset_freq: f32) {
+        *self = Self {
+            offset_freq,
+            ..Default::default()
+        };
+    }
+    /// Starts a note lasting `on_len` samples until note-off and `life` samples in total.
+    pub fn start(&mut self, instance: &VoiceInstance, on_len: i32, life: i32) {
+        self.life = life;
+        if life > 0 {
+            self.on = on_len;
+            self.pos = 0.;
+            self.env_pos = 0;
+            self.env_volume = if instance.envelope.is_empty() { 128 } else { 0 };
+            self.env_start = self.env_volume;
+        }
+    }
+
+    /// Silences the tone immediately.
+    pub fn kill(&mut self) {
+        self.life = 0;
+    }
+
+    /// Returns whether the tone is still sounding.
+    #[inline]
+    pub fn is_alive(&self) -> bool {
+        self.life > 0
+    }
+
+    /// Updates envelope volume for the current sample.
+    pub fn update_envelope(&mut self, instance: &VoiceInstance) {
+        if !self.is_alive() || instance.envelope.is_empty() {
+            return;
+        }
+        if self.on > 0 {
+            // Attack, sustaining the last value.
+            if let Some(&volume) = instance.envelope.get(self.env_pos as usize) {
+                self.env_volume = volume as i32;
+                self.env_pos += 1;
+            }
+        } else if instance.release_len > 0 {
+            // Release.
+            self.env_volume = self.env_start - self.env_start * self.env_pos / instance.release_len;
+            self.env_pos += 1;
+        }
+    }
+
+    /// Returns the current sample for channel `ch` (0 = left, 1 = right) with envelope and
+    /// smoothing applied, scaled by the given velocity & volume (out of 128) and channel pan
+    /// volume (out of 64).
+    pub fn sample(
+        &self,
+        instance: &VoiceInstance,
+        ch: usize,
+        velocity: i32,
+        volume: i32,
+        pan: i32,
+        smooth_len: i32,
+    ) -> i32 {
+        if !self.is_alive() {
+            return 0;
+        }
+        let Some(samples) = instance.samples.get(self.pos as usize) else {
+            return 0;
+        };
+
+        let mut work = samples[ch] as i32;
+        work = work * velocity / 128;
+        work = work * volume / 128;
+        work = work * pan / 64;
+        if !instance.envelope.is_empty() {
+            work = work * self.env_volume / 128;
+        }
+        // Slight fadeout at the very end of the tone.
+        if instance.flags.smooth && (self.life < smooth_len) {
+            work = work * self.life / smooth_len;
+        }
+
+        work
+    }
+
+    /// Advances the tone by one output sample, moving through the sample table by `step` times its
+    /// step at the default key.
+    pub fn increment(&mut self, instance: &VoiceInstance, step: f32) {
+        if self.life > 0 {
+            self.life -= 1;
+        }
+        if !self.is_alive() {
+            return;
+        }
+
+        self.on -= 1;
+        self.pos += (self.offset_freq * step) as f64;
+
+        let len = instance.samples.len() as f64;
+        if self.pos >= len {
+            if instance.flags.wave_loop && (len > 0.) {
+                self.pos %= len;
+            } else {
+                self.life = 0;
+            }
+        }
+
+        // Note-off starts the release from the current envelope volume.
+        if (self.on == 0) && !instance.envelope.is_empty() {
+            self.env_start = self.env_volume;
+            self.env_pos = 0;
+        }
+    }
+}
+
+/// Renders a single note of the given voice-units as interleaved stereo samples, mixing every unit
+/// as pxtone would. See [`Voice::render_note`].
+pub(crate) fn render_note<S: Sample>(
+    instances: &[VoiceInstance],
+    key: Key,
+    velocity: Volume,
+    length: f32,
+    sample_rate: u32,
+) -> Box<[S]> {
+    const TEMPO: f32 = 120.;
+
+    let on_len = ((length.max(0.) as f64) * (sample_rate as f64)) as i32;
+    let step = key_freq(key.as_a4_offset()) * (BASE_SAMPLE_RATE as f32) / (sample_rate as f32);
+    let smooth_len = smooth_len(sample_rate);
+
+    let mut tones = instances
+        .iter()
+        .map(|instance| {
+            let mut tone = Tone::default();
+            tone.reset(instance.offset_freq(TEMPO));
+            tone.start(instance, on_len, on_len + instance.release_len);
+            tone
+        })
+        .collect::<Vec<_>>();
+
+    let mut output = vec![];
+    while tones.iter().any(Tone::is_alive) {
+        for (instance, tone) in instances.iter().zip(tones.iter_mut()) {
+            tone.update_envelope(instance);
+        }
+        for ch in 0..2 {
+            let work = instances
+                .iter()
+                .zip(tones.iter())
+                .map(|(instance, tone)| {
+                    tone.sample(instance, ch, velocity.as_value(), 128, 64, smooth_len)
+                })
+                .sum::<i32>();
+            output.push(S::from_i16(work.clamp(-32767, 32767) as i16));
+        }
+        for (instance, tone) in instances.iter().zip(tones.iter_mut()) {
+            tone.increment(instance, step);
+        }
+    }
+
+    output.into_boxed_slice()
+}
+
+/// Returns the number of samples that smoothed tones fade out over.
+#[inline]
+pub(crate) fn smooth_len(sample_rate: u32) -> i32 {
+    (sample_rate / 250) as i32
+}
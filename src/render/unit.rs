@@ -0,0 +1,215 @@
+use super::tone::{Tone, VoiceInstance, BASE_SAMPLE_RATE};
+use crate::project::{Note, Project};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Number of unit groups that are mixed separately.
+pub(crate) const GROUP_COUNT: usize = Project::GROUP_COUNT;
+/// Length of the buffer used to delay one stereo channel for pan-time.
+pub(crate) const TIME_PAN_LEN: usize = 64;
+
+/// Default unit state at the start of playback.
+const DEFAULT_VOLUME: i32 = 104;
+const DEFAULT_PAN: i32 = 64;
+const DEFAULT_TUNING: f32 = 1.;
+
+/// Playback state of a single project unit.
+#[derive(Clone, Debug)]
+pub(crate) struct UnitState {
+    /// Index of the voice played by this unit, if any.
+    pub voice: Option<usize>,
+    /// One tone per voice-unit of the current voice, with room for the largest voice.
+    tones: Box<[Tone]>,
+
+    key_now: i32,
+    key_start: i32,
+    key_margin: i32,
+    portamento_len: i32,
+    portamento_pos: i32,
+
+    pub velocity: i32,
+    pub volume: i32,
+    pub tuning: f32,
+    pub group: usize,
+    pan_volumes: [i32; 2],
+    /// Delay in samples for each channel.
+    pan_times: [usize; 2],
+    /// Recent output of each channel, for pan-time.
+    pan_time_buf: [[i32; 2]; TIME_PAN_LEN],
+}
+
+impl UnitState {
+    /// Creates unit state able to play voices with up to `max_tones` voice-units.
+    pub fn new(max_tones: usize) -> Self {
+        let mut unit = Self {
+            voice: None,
+            tones: vec![Tone::default(); max_tones].into_boxed_slice(),
+
+            key_now: 0,
+            key_start: 0,
+            key_margin: 0,
+            portamento_len: 0,
+            portamento_pos: 0,
+
+            velocity: 0,
+            volume: 0,
+            tuning: 0.,
+            group: 0,
+            pan_volumes: [0; 2],
+            pan_times: [0; 2],
+            pan_time_buf: [[0; 2]; TIME_PAN_LEN],
+        };
+        unit.init();
+        unit
+    }
+
+    /// Restores the state of a newly created unit.
+    pub fn reset(&mut self) {
+        self.voice = None;
+        self.zero_lives();
+        self.key_now = 0;
+        self.key_start = 0;
+        self.key_margin = 0;
+        self.init();
+    }
+
+    /// Restores the default state at the start of playback, keeping the current key so that it can
+    /// be slid from.
+    pub fn init(&mut self) {
+        self.velocity = Note::DEFAULT_VELOCITY.as_value();
+        self.volume = DEFAULT_VOLUME;
+        self.tuning = DEFAULT_TUNING;
+        self.group = 0;
+        self.portamento_len = 0;
+        self.portamento_pos = 0;
+        self.pan_time_buf = [[0; 2]; TIME_PAN_LEN];
+
+        self.set_key(Note::DEFAULT_KEY.as_value());
+        self.set_pan_volume(DEFAULT_PAN);
+        self.set_pan_time(DEFAULT_PAN, BASE_SAMPLE_RATE);
+    }
+
+    /// Switches to the given voice, silencing all tones.
+    pub fn set_voice(&mut self, index: usize, instances: &[VoiceInstance], tempo: f32) {
+        self.voice = Some(index);
+        for (tone, instance) in self.tones.iter_mut().zip(instances) {
+            tone.reset(instance.offset_freq(tempo));
+        }
+    }
+
+    /// Returns tones paired with the voice-units they play.
+    pub fn tones_mut<'a>(
+        &'a mut self,
+        voices: &'a [Box<[VoiceInstance]>],
+    ) -> impl Iterator<Item = (&'a mut Tone, &'a VoiceInstance)> {
+        let instances = match self.voice {
+            Some(index) => &voices[index][..],
+            None => &[],
+        };
+        self.tones.iter_mut().zip(instances)
+    }
+
+    /// Finishes any key slide so that the next note starts at the target key.
+    pub fn key_on(&mut self) {
+        self.key_now = self.key_start + self.key_margin;
+        self.key_start = self.key_now;
+        self.key_margin = 0;
+    }
+    /// Silences all tones immediately.
+    pub fn zero_lives(&mut self) {
+        for tone in self.tones.iter_mut() {
+            tone.kill();
+        }
+    }
+
+    /// Starts sliding to the given key.
+    pub fn set_key(&mut self, key: i32) {
+        self.key_start = self.key_now;
+        self.key_margin = key - self.key_start;
+        self.portamento_pos = 0;
+    }
+    /// Sets the duration of key slides in samples.
+    pub fn set_portamento(&mut self, len: i32) {
+        self.portamento_len = len;
+    }
+    /// Sets stereo channel volumes, where 64 = center.
+    pub fn set_pan_volume(&mut self, pan: i32) {
+        self.pan_volumes = [64, 64];
+        if pan >= 64 {
+            self.pan_volumes[0] = 128 - pan;
+        } else {
+            self.pan_volumes[1] = pan;
+        }
+    }
+    /// Sets stereo channel delays, where 64 = center.
+    pub fn set_pan_time(&mut self, pan: i32, sample_rate: u32) {
+        let (ch, delay) = if pan >= 64 {
+            (0, pan - 64)
+        } else {
+            (1, 64 - pan)
+        };
+        self.pan_times = [0, 0];
+        self.pan_times[ch] = ((delay.min(63) as u32) * BASE_SAMPLE_RATE / sample_rate) as usize;
+    }
+
+    /// Updates envelope volume of every tone for the current sample.
+    pub fn update_envelope(&mut self, voices: &[Box<[VoiceInstance]>]) {
+        for (tone, instance) in self.tones_mut(voices) {
+            tone.update_envelope(instance);
+        }
+    }
+
+    /// Mixes every tone for the current sample into the pan-time buffer.
+    pub fn sample(
+        &mut self,
+        voices: &[Box<[VoiceInstance]>],
+        time_pan_index: usize,
+        smooth_len: i32,
+    ) {
+        let Some(instances) = self.voice.map(|index| &voices[index]) else {
+            return;
+        };
+
+        for ch in 0..2 {
+            self.pan_time_buf[time_pan_index][ch] = self
+                .tones
+                .iter()
+                .zip(instances.iter())
+                .map(|(tone, instance)| {
+                    let pan = self.pan_volumes[ch];
+                    tone.sample(instance, ch, self.velocity, self.volume, pan, smooth_len)
+                })
+                .sum();
+        }
+    }
+    /// Returns the delayed output of channel `ch` for the current sample.
+    pub fn output(&self, ch: usize, time_pan_index: usize) -> i32 {
+        let index = time_pan_index.wrapping_sub(self.pan_times[ch]) % TIME_PAN_LEN;
+        self.pan_time_buf[index][ch]
+    }
+
+    /// Advances any key slide by one sample, returning the resulting key.
+    pub fn increment_key(&mut self) -> i32 {
+        if (self.portamento_len != 0) && (self.key_margin != 0) {
+            if self.portamento_pos < self.portamento_len {
+                self.portamento_pos += 1;
+                self.key_now = (self.key_start as f64
+                    + (self.key_margin as f64) * (self.portamento_pos as f64)
+                        / (self.portamento_len as f64)) as i32;
+            } else {
+                self.key_on();
+            }
+        } else {
+            self.key_now = self.key_start + self.key_margin;
+        }
+        self.key_now
+    }
+    /// Advances every tone by one sample, where `freq` is the frequency ratio of the current key
+    /// scaled to the output sample rate.
+    pub fn increment_sample(&mut self, voices: &[Box<[VoiceInstance]>], freq: f32) {
+        let step = self.tuning * freq;
+        for (tone, instance) in self.tones_mut(voices) {
+            tone.increment(instance, step);
+        }
+    }
+}
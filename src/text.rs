@@ -0,0 +1,237 @@
+//! Shift-JIS text, which pxtone uses for project names & comments as well as unit & voice names.
+//!
+//! Shift-JIS is supported as defined by the JIS X 0208 standard, without any vendor extensions.
+
+mod error;
+mod table;
+pub use self::error::*;
+
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+use std::sync::OnceLock;
+
+//--------------------------------------------------------------------------------------------------
+
+/// Text decoded from Shift-JIS, or the original bytes if they aren't valid Shift-JIS. Raw bytes are
+/// kept as-is, so no data is lost by decoding & re-encoding text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Text {
+    /// Successfully decoded text.
+    String(String),
+    /// Bytes that couldn't be decoded.
+    Raw(Box<[u8]>),
+}
+
+impl Text {
+    /// Decodes Shift-JIS bytes, falling back to the raw bytes if they aren't valid.
+    ///
+    /// ```
+    /// # use ptcrab::text::Text;
+    /// assert_eq!(Text::decode(b"\x83s\x83X\x83g\x83\x93"), Text::from("ピストン"));
+    /// assert_eq!(Text::decode(b"\x83"), Text::Raw(Box::new([0x83])));
+    /// ```
+    pub fn decode(bytes: &[u8]) -> Self {
+        match decode(bytes) {
+            Some(string) => Self::String(string),
+            None => Self::Raw(bytes.into()),
+        }
+    }
+    /// Decodes a fixed-width field of Shift-JIS bytes, which ends at the first null byte (if any).
+    pub fn decode_fixed(bytes: &[u8]) -> Self {
+        let len = bytes
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(bytes.len());
+        Self::decode(&bytes[..len])
+    }
+
+    /// Encodes the text as Shift-JIS. Raw bytes are returned unchanged.
+    pub fn encode(&self) -> Result<Box<[u8]>, TextError> {
+        match self {
+            Self::String(string) => encode(string),
+            Self::Raw(bytes) => Ok(bytes.clone()),
+        }
+    }
+    /// Encodes the text as a fixed-width field of `N` Shift-JIS bytes, padded with null bytes.
+    ///
+    /// Text that's too long is truncated to as many whole characters as fit, so a double-byte
+    /// character is never split in half. Raw bytes are simply cut off.
+    ///
+    /// ```
+    /// # use ptcrab::text::Text;
+    /// let text = Text::from("ドラム");
+    /// assert_eq!(text.encode_fixed::<8>()?, *b"\x83h\x83\x89\x83\x80\0\0");
+    /// assert_eq!(text.encode_fixed::<5>()?, *b"\x83h\x83\x89\0");
+    /// # Ok::<(), ptcrab::text::TextError>(())
+    /// ```
+    pub fn encode_fixed<const N: usize>(&self) -> Result<[u8; N], TextError> {
+        let mut field = [0; N];
+        match self {
+            Self::String(string) => {
+                let mut len = 0;
+                for c in string.chars() {
+                    let (bytes, char_len) = encode_char(c)?;
+                    if len + char_len > N {
+                        break;
+                    }
+                    field[len..(len + char_len)].copy_from_slice(&bytes[..char_len]);
+                    len += char_len;
+                }
+            }
+            Self::Raw(bytes) => {
+                let len = bytes.len().min(N);
+                field[..len].copy_from_slice(&bytes[..len]);
+            }
+        }
+        Ok(field)
+    }
+
+    /// Returns the text as a string, replacing any undecodable bytes with
+    /// [`U+FFFD REPLACEMENT CHARACTER`](char::REPLACEMENT_CHARACTER).
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        match self {
+            Self::String(string) => Cow::Borrowed(string),
+            Self::Raw(bytes) => Cow::Owned(decode_lossy(bytes)),
+        }
+    }
+}
+
+impl Display for Text {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_lossy())
+    }
+}
+
+impl From<String> for Text {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+impl From<&str> for Text {
+    fn from(value: &str) -> Self {
+        Self::String(value.into())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Number of trail bytes per lead byte in the double-byte table.
+const TRAIL_COUNT: usize = 188;
+
+/// Decodes Shift-JIS bytes, returning [`None`] if they aren't valid.
+pub fn decode(bytes: &[u8]) -> Option<String> {
+    let mut string = String::with_capacity(bytes.len());
+    let mut iter = bytes.iter();
+    while let Some(&byte) = iter.next() {
+        let c = match decode_single(byte) {
+            Some(c) => c,
+            None => decode_double(byte, *iter.next()?)?,
+        };
+        string.push(c);
+    }
+    Some(string)
+}
+
+/// Decodes Shift-JIS bytes, replacing anything invalid with
+/// [`U+FFFD REPLACEMENT CHARACTER`](char::REPLACEMENT_CHARACTER).
+fn decode_lossy(bytes: &[u8]) -> String {
+    let mut string = String::with_capacity(bytes.len());
+    let mut index = 0;
+    while let Some(&byte) = bytes.get(index) {
+        let (c, len) = match decode_single(byte) {
+            Some(c) => (c, 1),
+            // An invalid trail byte is decoded again on its own, in case it's valid by itself.
+            None => match bytes
+                .get(index + 1)
+                .and_then(|&trail| decode_double(byte, trail))
+            {
+                Some(c) => (c, 2),
+                None => (char::REPLACEMENT_CHARACTER, 1),
+            },
+        };
+        string.push(c);
+        index += len;
+    }
+    string
+}
+
+/// Encodes a string as Shift-JIS, failing if any character can't be represented.
+///
+/// ```
+/// # use ptcrab::text::{self, TextError};
+/// assert_eq!(text::encode("ｶﾆ")?.as_ref(), b"\xB6\xC6");
+/// assert_eq!(text::encode("🦀"), Err(TextError::Unencodable('🦀')));
+/// # Ok::<(), TextError>(())
+/// ```
+pub fn encode(string: &str) -> Result<Box<[u8]>, TextError> {
+    let mut bytes = Vec::with_capacity(string.len());
+    for c in string.chars() {
+        let (char_bytes, len) = encode_char(c)?;
+        bytes.extend_from_slice(&char_bytes[..len]);
+    }
+    Ok(bytes.into_boxed_slice())
+}
+
+/// Decodes a single-byte character: ASCII or half-width katakana.
+fn decode_single(byte: u8) -> Option<char> {
+    match byte {
+        0x00..=0x7F => Some(byte as char),
+        0xA1..=0xDF => char::from_u32(0xFF61 + (byte - 0xA1) as u32),
+        _ => None,
+    }
+}
+
+/// Decodes a double-byte character.
+fn decode_double(lead: u8, trail: u8) -> Option<char> {
+    let lead_index = match lead {
+        0x81..=0x9F => lead - 0x81,
+        0xE0..=0xEA => lead - 0xE0 + 0x1F,
+        _ => return None,
+    } as usize;
+    let trail_index = match trail {
+        0x40..=0x7E => trail - 0x40,
+        0x80..=0xFC => trail - 0x41,
+        _ => return None,
+    } as usize;
+
+    let code = table::DOUBLE_BYTE[(lead_index * TRAIL_COUNT) + trail_index];
+    (code != 0).then(|| char::from_u32(code as u32)).flatten()
+}
+
+/// Encodes a single character, returning its bytes & length.
+fn encode_char(c: char) -> Result<([u8; 2], usize), TextError> {
+    match c as u32 {
+        0x00..=0x7F => return Ok(([c as u8, 0], 1)),
+        code @ 0xFF61..=0xFF9F => return Ok(([(code - 0xFF61) as u8 + 0xA1, 0], 1)),
+        _ => {}
+    }
+
+    // Look up double-byte characters in a table sorted by code point.
+    static ENCODE_TABLE: OnceLock<Box<[(u16, u16)]>> = OnceLock::new();
+    let table = ENCODE_TABLE.get_or_init(|| {
+        let mut table: Box<[_]> = (table::DOUBLE_BYTE.iter().enumerate())
+            .filter(|(_, &code)| code != 0)
+            .map(|(index, &code)| (code, index as u16))
+            .collect();
+        table.sort_unstable();
+        table
+    });
+    let code = u16::try_from(c as u32).map_err(|_| TextError::Unencodable(c))?;
+    let index = table
+        .binary_search_by_key(&code, |&(code, _)| code)
+        .map_err(|_| TextError::Unencodable(c))?;
+
+    let index = table[index].1 as usize;
+    let (lead_index, trail_index) = ((index / TRAIL_COUNT) as u8, (index % TRAIL_COUNT) as u8);
+    let lead = if lead_index < 0x1F {
+        lead_index + 0x81
+    } else {
+        lead_index - 0x1F + 0xE0
+    };
+    let trail = if trail_index < 0x3F {
+        trail_index + 0x40
+    } else {
+        trail_index + 0x41
+    };
+    Ok(([lead, trail], 2))
+}
@@ -0,0 +1,11 @@
+use thiserror::Error as ThisError;
+
+//--------------------------------------------------------------------------------------------------
+
+/// Errors arising from Shift-JIS text encoding.
+#[derive(Debug, PartialEq, Eq, ThisError)]
+pub enum TextError {
+    /// Text contains a character that Shift-JIS can't represent.
+    #[error("character {0:?} can't be encoded as shift-jis")]
+    Unencodable(char),
+}
@@ -13,6 +13,11 @@ impl Volume {
         Self::from((128. * ratio) as i32)
     }
 
+    /// Converts from volume value in const contexts, where [`From`] can't be used.
+    pub(crate) const fn from_value(value: i32) -> Self {
+        Self(value)
+    }
+
     /// Returns volume value as [`i32`].
     pub const fn as_value(&self) -> i32 {
         self.0
@@ -25,7 +30,7 @@ impl Volume {
 
 impl From<i32> for Volume {
     fn from(value: i32) -> Self {
-        Self(value)
+        Self::from_value(value)
     }
 }
 
@@ -1,15 +1,141 @@
 //! General functionality for all voice types.
 
+mod error;
 mod flags;
+mod ogg;
+mod params;
+mod pcm;
+mod ptn;
+pub use self::error::*;
 pub use self::flags::*;
+pub use self::ogg::*;
+pub use self::params::*;
+pub use self::pcm::*;
+pub use self::ptn::*;
 
 use crate::ptvoice::Ptvoice;
+use crate::render::tone::{self, VoiceInstance};
+use crate::render::Sample;
+use crate::{Key, PanVolume, Volume};
 
 //--------------------------------------------------------------------------------------------------
 
 /// A single reusable sample or synthesized instrument.
+#[derive(Clone, Debug, PartialEq)]
 pub enum Voice {
     Ptv(Ptvoice),
+    Ptn(PtnVoice),
+    Pcm(PcmVoice),
+    Ogg(OggVoice),
+}
+
+/// Type of voice data, which determines how a voice is stored in a project.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoiceKind {
+    /// Ptvoice (`matePTV ` block).
+    Ptv,
+    /// Ptnoise (`matePTN ` block).
+    Ptn,
+    /// Uncompressed PCM sample (`matePCM ` block).
+    Pcm,
+    /// Ogg Vorbis-compressed sample (`mateOGGV` block).
+    Ogg,
+}
+
+impl Voice {
+    /// Returns the type of voice data.
+    pub fn kind(&self) -> VoiceKind {
+        match self {
+            Self::Ptv(_) => VoiceKind::Ptv,
+            Self::Ptn(_) => VoiceKind::Ptn,
+            Self::Pcm(_) => VoiceKind::Pcm,
+            Self::Ogg(_) => VoiceKind::Ogg,
+        }
+    }
+
+    /// Returns the number of voice-units, each of which is played at the same time. Only ptvoices
+    /// can have more (or less) than one.
+    pub fn unit_count(&self) -> usize {
+        match self {
+            Self::Ptv(ptv) => ptv.units.len(),
+            Self::Ptn(_) | Self::Pcm(_) | Self::Ogg(_) => 1,
+        }
+    }
+
+    /// Returns the playback parameters of the voice-unit at the given index, if it exists.
+    ///
+    /// ```
+    /// # use ptcrab::voice::{PcmVoice, Voice};
+    /// # use ptcrab::{Key, Volume};
+    /// let voice = Voice::from(PcmVoice::new(1, 44100, 16, Box::new([])));
+    /// let params = voice.unit_params(0).unwrap();
+    /// assert_eq!(params.inherent_key, Key::A4);
+    /// assert_eq!(params.volume, Volume::from(128));
+    /// assert!(voice.unit_params(1).is_none());
+    /// ```
+    pub fn unit_params(&self, index: usize) -> Option<VoiceParams> {
+        let (inherent_key, tuning, flags) = match self {
+            Self::Ptv(ptv) => {
+                let unit = ptv.units.get(index)?;
+                return Some(VoiceParams {
+                    inherent_key: unit.inherent_key,
+                    volume: unit.volume,
+                    pan_volume: unit.pan_volume,
+                    tuning: unit.tuning,
+                    flags: unit.flags.clone(),
+                });
+            }
+            _ if index != 0 => return None,
+            Self::Ptn(ptn) => (ptn.inherent_key, ptn.tuning, &ptn.flags),
+            Self::Pcm(pcm) => (pcm.inherent_key, pcm.tuning, &pcm.flags),
+            Self::Ogg(ogg) => (ogg.inherent_key, ogg.tuning, &ogg.flags),
+        };
+
+        Some(VoiceParams {
+            inherent_key,
+            volume: Volume::default(),
+            pan_volume: PanVolume::CENTER,
+            tuning,
+            flags: flags.clone(),
+        })
+    }
+    /// Returns the playback parameters of every voice-unit.
+    pub fn all_unit_params(&self) -> impl Iterator<Item = VoiceParams> + '_ {
+        (0..self.unit_count()).filter_map(|index| self.unit_params(index))
+    }
+
+    /// Returns whether any voice-unit repeats its waveform for the full duration of a note.
+    pub fn is_looped(&self) -> bool {
+        self.all_unit_params().any(|params| params.flags.wave_loop)
+    }
+
+    /// Renders a single note as interleaved stereo samples, mixing every voice-unit as pxtone
+    /// would.
+    ///
+    /// The note is held for `length` seconds, after which each unit's envelope release plays out.
+    /// Samples that don't loop stop once they reach their end, which may be sooner. Beat-fit units
+    /// are stretched to one beat at 120 BPM, pxtone's default tempo. Ogg Vorbis voices are silent
+    /// unless the `ogg` feature is enabled.
+    ///
+    /// ```
+    /// # use ptcrab::voice::{PcmVoice, Voice};
+    /// # use ptcrab::{Key, Volume};
+    /// // One second of a 16-bit mono sample, played an octave up.
+    /// let pcm = PcmVoice::new(1, 44100, 16, vec![0; 2 * 44100].into_boxed_slice());
+    /// let voice = Voice::from(pcm);
+    /// let samples = voice.render_note::<f32>(Key::from(Key::A4.as_value() + 12 * 256), Volume::from(128), 2., 44100);
+    /// assert_eq!(samples.len(), 2 * 22050);
+    /// ```
+    pub fn render_note<S: Sample>(
+        &self,
+        key: Key,
+        velocity: Volume,
+        length: f32,
+        sample_rate: u32,
+    ) -> Box<[S]> {
+        let instances = VoiceInstance::from_voice(self, sample_rate);
+        tone::render_note(&instances, key, velocity, length, sample_rate)
+    }
 }
 
 impl From<Ptvoice> for Voice {
@@ -17,3 +143,18 @@ impl From<Ptvoice> for Voice {
         Self::Ptv(value)
     }
 }
+impl From<PtnVoice> for Voice {
+    fn from(value: PtnVoice) -> Self {
+        Self::Ptn(value)
+    }
+}
+impl From<PcmVoice> for Voice {
+    fn from(value: PcmVoice) -> Self {
+        Self::Pcm(value)
+    }
+}
+impl From<OggVoice> for Voice {
+    fn from(value: OggVoice) -> Self {
+        Self::Ogg(value)
+    }
+}
@@ -0,0 +1,28 @@
+use std::io::Error as IoError;
+use thiserror::Error as ThisError;
+
+//--------------------------------------------------------------------------------------------------
+
+/// Errors arising from WAV import/export.
+#[derive(Debug, ThisError)]
+pub enum WavError {
+    /// WAV data uses a sample format that pxtone doesn't support.
+    #[error("unsupported wav format")]
+    Unsupported,
+    /// WAV data is malformed or missing required chunks.
+    #[error("invalid wav data")]
+    Invalid,
+    /// Sample data is too long to encode its own length.
+    #[error("wav data exceeds max size")]
+    Oversized,
+
+    /// I/O error while reading/writing WAV data.
+    #[error("wav I/O failure: {0}")]
+    IoFailure(IoError),
+}
+
+impl From<IoError> for WavError {
+    fn from(value: IoError) -> Self {
+        Self::IoFailure(value)
+    }
+}
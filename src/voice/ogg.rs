@@ -0,0 +1,227 @@
+use super::VoiceFlags;
+use crate::{Key, Tuning};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Properties of an Ogg Vorbis stream, read from its headers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VorbisInfo {
+    /// Number of channels.
+    pub channels: u8,
+    /// Sample rate in hertz.
+    pub sample_rate: u32,
+    /// Total number of samples per channel, according to the granule position of the last page.
+    pub sample_count: u64,
+}
+
+/// Project voice made from Ogg Vorbis-compressed sample data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OggVoice {
+    /// Key at which the sample plays back at its original speed. Defaults to A<sub>4</sub>.
+    pub inherent_key: Key,
+    /// Tuning value for this voice.
+    pub tuning: Tuning,
+    /// Voice flags.
+    pub flags: VoiceFlags,
+
+    /// Number of channels, as recorded by pxtone.
+    pub channels: u32,
+    /// Sample rate in hertz, as recorded by pxtone.
+    pub sample_rate: u32,
+    /// Total number of samples per channel, as recorded by pxtone.
+    pub sample_count: u32,
+    /// Complete Ogg Vorbis stream.
+    pub data: Box<[u8]>,
+}
+
+impl OggVoice {
+    /// Creates a voice from a complete Ogg Vorbis stream using default parameters, recording the
+    /// stream's properties from its headers. Returns [`None`] if the stream is invalid (see
+    /// [`Self::stream_info`]).
+    ///
+    /// pxtone itself only plays mono or stereo streams.
+    pub fn new(data: Box<[u8]>) -> Option<Self> {
+        let info = read_stream_info(&data)?;
+        Some(Self {
+            inherent_key: Key::A4,
+            tuning: Tuning::default(),
+            flags: VoiceFlags {
+                wave_loop: false,
+                smooth: true,
+                beat_fit: false,
+            },
+
+            channels: info.channels as u32,
+            sample_rate: info.sample_rate,
+            sample_count: info.sample_count.try_into().ok()?,
+            data,
+        })
+    }
+
+    /// Reads the properties of the voice's stream from its Vorbis identification header & last
+    /// page, without decoding any audio. Returns [`None`] if the data isn't made up of valid Ogg
+    /// pages (with correct checksums) starting with a Vorbis identification header.
+    ///
+    /// The result can be compared with [`Self::channels`], [`Self::sample_rate`] &
+    /// [`Self::sample_count`] to check that they're consistent with the stream.
+    ///
+    /// ```
+    /// # use ptcrab::voice::{OggVoice, VorbisInfo};
+    /// let mut data = vec![
+    ///     // First page, marked as the beginning of the stream, with one 30-byte segment.
+    ///     0x4F, 0x67, 0x67, 0x53, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ///     0x52, 0x43, 0x54, 0x50, 0x00, 0x00, 0x00, 0x00, 0x1C, 0x16, 0xCD, 0xEB, 0x01, 0x1E,
+    ///     // Vorbis identification header: 2 channels at 44100 Hz.
+    ///     0x01, 0x76, 0x6F, 0x72, 0x62, 0x69, 0x73, 0x00, 0x00, 0x00, 0x00, 0x02, 0x44, 0xAC, 0x00,
+    ///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xB8, 0x01,
+    ///     // Last page, marked as the end of the stream, at granule position 44100.
+    ///     0x4F, 0x67, 0x67, 0x53, 0x00, 0x04, 0x44, 0xAC, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ///     0x52, 0x43, 0x54, 0x50, 0x01, 0x00, 0x00, 0x00, 0x31, 0xF1, 0x77, 0x9F, 0x00,
+    /// ];
+    /// let voice = OggVoice::new(data.clone().into()).unwrap();
+    /// assert_eq!(
+    ///     voice.stream_info(),
+    ///     Some(VorbisInfo { channels: 2, sample_rate: 44100, sample_count: 44100 })
+    /// );
+    ///
+    /// // Pages with incorrect checksums are rejected.
+    /// data[40] ^= 1;
+    /// assert_eq!(OggVoice::new(data.into()), None);
+    /// ```
+    pub fn stream_info(&self) -> Option<VorbisInfo> {
+        read_stream_info(&self.data)
+    }
+
+    /// Decodes sample data into 16-bit stereo frames at the given sample rate, the same way pxtone
+    /// prepares samples for playback. Returns [`None`] if decoding fails or the stream has more
+    /// than 2 channels.
+    #[cfg(feature = "ogg")]
+    pub(crate) fn to_stereo_frames(&self, sample_rate: u32) -> Option<Box<[[i16; 2]]>> {
+        use lewton::inside_ogg::OggStreamReader;
+        use std::io::Cursor;
+
+        let mut reader = OggStreamReader::new(Cursor::new(&self.data)).ok()?;
+        let channels = reader.ident_hdr.audio_channels;
+        let stream_rate = reader.ident_hdr.audio_sample_rate;
+
+        let mut frames = vec![];
+        while let Some(samples) = reader.read_dec_packet_itl().ok()? {
+            match channels {
+                1 => frames.extend(samples.into_iter().map(|value| [value; 2])),
+                2 => frames.extend(samples.chunks_exact(2).map(|frame| [frame[0], frame[1]])),
+                _ => return None,
+            }
+        }
+
+        Some(super::resample_frames(&frames, stream_rate, sample_rate))
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+
+/// Single page of an Ogg bitstream.
+struct OggPage<'a> {
+    header_type: u8,
+    /// Codec-specific position, or [`None`] if no packet ends on this page.
+    granule_position: Option<u64>,
+    serial: u32,
+    /// Concatenated packet data.
+    body: &'a [u8],
+    /// Size of the first packet (or part of one) in the body.
+    first_packet_len: usize,
+}
+
+impl<'a> OggPage<'a> {
+    const CAPTURE_PATTERN: &'static [u8; 4] = b"OggS";
+    /// Header type flag for the first page of a logical bitstream.
+    const BEGINNING_OF_STREAM: u8 = 0x02;
+
+    /// Reads the page at the start of `data`, returning it along with the remaining data.
+    fn read(data: &'a [u8]) -> Option<(Self, &'a [u8])> {
+        let header = data.get(..27)?;
+        if (&header[0..4] != Self::CAPTURE_PATTERN) || (header[4] != 0) {
+            return None;
+        }
+        let segment_count = header[26] as usize;
+        let segments = data.get(27..(27 + segment_count))?;
+        let header_len = 27 + segment_count;
+        let body_len = segments.iter().map(|&len| len as usize).sum::<usize>();
+        let page = data.get(..(header_len + body_len))?;
+
+        // Checksum is calculated with its own field zeroed.
+        let checksum = u32::from_le_bytes(page[22..26].try_into().ok()?);
+        let crc = [&page[..22], &[0; 4], &page[26..]]
+            .into_iter()
+            .flatten()
+            .fold(0, |crc, &byte| ogg_crc_step(crc, byte));
+        if crc != checksum {
+            return None;
+        }
+
+        let granule_position = u64::from_le_bytes(page[6..14].try_into().ok()?);
+        let first_packet_len = segments
+            .iter()
+            .position(|&len| len < 255)
+            .map_or(body_len, |end| 255 * end + segments[end] as usize);
+
+        Some((
+            Self {
+                header_type: header[5],
+                granule_position: Some(granule_position).filter(|&pos| pos != u64::MAX),
+                serial: u32::from_le_bytes(page[14..18].try_into().ok()?),
+                body: &page[header_len..],
+                first_packet_len,
+            },
+            &data[page.len()..],
+        ))
+    }
+}
+
+/// Updates an Ogg CRC-32 checksum (polynomial 0x04C11DB7, unreflected) with one byte.
+fn ogg_crc_step(crc: u32, byte: u8) -> u32 {
+    (0..8).fold(crc ^ ((byte as u32) << 24), |crc, _| {
+        (crc << 1)
+            ^ (if (crc & (1 << 31)) != 0 {
+                0x04C11DB7
+            } else {
+                0
+            })
+    })
+}
+
+/// Reads the properties of an Ogg Vorbis stream. See [`OggVoice::stream_info`].
+fn read_stream_info(data: &[u8]) -> Option<VorbisInfo> {
+    let (first_page, mut data) = OggPage::read(data)?;
+    if (first_page.header_type & OggPage::BEGINNING_OF_STREAM) == 0 {
+        return None;
+    }
+
+    // Identification header packet.
+    let header = first_page.body.get(..first_page.first_packet_len)?;
+    if (header.len() < 30) || (&header[0..7] != b"\x01vorbis") {
+        return None;
+    }
+    let version = u32::from_le_bytes(header[7..11].try_into().ok()?);
+    let channels = header[11];
+    let sample_rate = u32::from_le_bytes(header[12..16].try_into().ok()?);
+    let framing = header[29] & 1;
+    if (version != 0) || (channels == 0) || (sample_rate == 0) || (framing == 0) {
+        return None;
+    }
+
+    // The last granule position of the stream is its total length in samples.
+    let mut sample_count = first_page.granule_position.unwrap_or(0);
+    while !data.is_empty() {
+        let (page, rest) = OggPage::read(data)?;
+        if page.serial == first_page.serial {
+            sample_count = page.granule_position.unwrap_or(sample_count);
+        }
+        data = rest;
+    }
+
+    Some(VorbisInfo {
+        channels,
+        sample_rate,
+        sample_count,
+    })
+}
@@ -0,0 +1,19 @@
+use super::VoiceFlags;
+use crate::{Key, PanVolume, Tuning, Volume};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Playback parameters of a single voice-unit, common to every type of voice.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoiceParams {
+    /// Key at which the voice-unit plays back at its original speed.
+    pub inherent_key: Key,
+    /// Volume of the voice-unit. Sampled voices always play at full volume (128).
+    pub volume: Volume,
+    /// Relative stereo channel volumes of the voice-unit. Sampled voices are always centered.
+    pub pan_volume: PanVolume,
+    /// Tuning value of the voice-unit.
+    pub tuning: Tuning,
+    /// Voice-unit flags.
+    pub flags: VoiceFlags,
+}
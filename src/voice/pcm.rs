@@ -0,0 +1,291 @@
+use super::{VoiceFlags, WavError};
+use crate::data::{FromRead, WriteTo};
+use crate::{Key, Tuning};
+
+use std::io::{ErrorKind, Read, Seek, Write};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Project voice made from uncompressed PCM sample data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PcmVoice {
+    /// Key at which the sample plays back at its original speed. Defaults to A<sub>4</sub>.
+    pub inherent_key: Key,
+    /// Tuning value for this voice.
+    pub tuning: Tuning,
+    /// Voice flags.
+    pub flags: VoiceFlags,
+
+    /// Number of interleaved channels (1 or 2).
+    pub channels: u16,
+    /// Sample rate in hertz.
+    pub sample_rate: u32,
+    /// Bits per sample (8 or 16). 8-bit samples are unsigned, while 16-bit samples are signed and
+    /// little-endian.
+    pub bits_per_sample: u16,
+    /// Raw interleaved sample data.
+    pub data: Box<[u8]>,
+}
+
+type ChunkTag = [u8; 4];
+
+impl PcmVoice {
+    /// WAV format tag for integer PCM.
+    const WAV_FORMAT_PCM: u16 = 0x0001;
+    /// WAV format tag for extensible formats, which specify the actual format separately.
+    const WAV_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+    /// Creates a voice from raw interleaved sample data, using default parameters.
+    pub fn new(channels: u16, sample_rate: u32, bits_per_sample: u16, data: Box<[u8]>) -> Self {
+        Self {
+            inherent_key: Key::A4,
+            tuning: Tuning::default(),
+            flags: VoiceFlags {
+                wave_loop: false,
+                smooth: true,
+                beat_fit: false,
+            },
+
+            channels,
+            sample_rate,
+            bits_per_sample,
+            data,
+        }
+    }
+
+    /// Returns whether pxtone can play sample data in this voice's format.
+    pub fn is_supported(&self) -> bool {
+        matches!(self.channels, 1 | 2) && matches!(self.bits_per_sample, 8 | 16)
+    }
+    /// Returns the number of bytes per sample frame (one sample for each channel).
+    pub fn block_align(&self) -> usize {
+        (self.channels as usize) * (self.bits_per_sample as usize / 8)
+    }
+    /// Returns the number of whole sample frames in the voice's data.
+    pub fn frame_count(&self) -> usize {
+        self.data.len().checked_div(self.block_align()).unwrap_or(0)
+    }
+
+    /// Creates a voice from a RIFF WAV file containing 8-bit or 16-bit integer PCM data with 1 or
+    /// 2 channels, using default parameters.
+    ///
+    /// Fails with [`WavError::Invalid`] if the file is malformed, including if a chunk is cut short
+    /// or the sample data ends part-way through a frame.
+    ///
+    /// ```
+    /// # use ptcrab::voice::{PcmVoice, WavError};
+    /// # use std::io::Cursor;
+    /// let voice = PcmVoice::new(2, 22050, 16, Box::new([0, 1, 2, 3, 4, 5, 6, 7]));
+    /// let mut wav = Cursor::new(vec![]);
+    /// voice.write_wav(&mut wav)?;
+    ///
+    /// wav.set_position(0);
+    /// assert_eq!(PcmVoice::from_wav(&mut wav)?, voice);
+    ///
+    /// // The format chunk is too short to hold a format.
+    /// let mut wav = Cursor::new(b"RIFF\x10\0\0\0WAVEfmt \x04\0\0\0\x01\0\x02\0".to_vec());
+    /// assert!(matches!(PcmVoice::from_wav(&mut wav), Err(WavError::Invalid)));
+    /// # Ok::<(), ptcrab::voice::WavError>(())
+    /// ```
+    pub fn from_wav<R: Read>(source: &mut R) -> Result<Self, WavError> {
+        if ChunkTag::from_read(source)? != *b"RIFF" {
+            return Err(WavError::Invalid);
+        }
+        let _ = u32::from_read(source)?;
+        if ChunkTag::from_read(source)? != *b"WAVE" {
+            return Err(WavError::Invalid);
+        }
+
+        // Read chunks until both format & data have been found, skipping any others.
+        let mut format = None;
+        let mut data = None;
+        while format.is_none() || data.is_none() {
+            let tag = ChunkTag::from_read(source)?;
+            let size = u32::from_read(source)?;
+            let mut chunk = source.take(size as u64);
+            match &tag {
+                b"fmt " => {
+                    // Running out of data within the chunk means it's too short.
+                    let result = Self::read_wav_format(&mut chunk).map_err(|error| match error {
+                        WavError::IoFailure(error) if error.kind() == ErrorKind::UnexpectedEof => {
+                            WavError::Invalid
+                        }
+                        error => error,
+                    });
+                    format = Some(result?);
+                }
+                b"data" => {
+                    let mut bytes = vec![];
+                    chunk.read_to_end(&mut bytes)?;
+                    data = Some(bytes.into_boxed_slice());
+                }
+                _ => {}
+            }
+            std::io::copy(&mut chunk, &mut std::io::sink())?;
+            if chunk.limit() != 0 {
+                return Err(WavError::Invalid);
+            }
+            // Chunks are padded to an even length, though the last chunk's padding isn't needed.
+            if !size.is_multiple_of(2) && (format.is_none() || data.is_none()) {
+                let _ = u8::from_read(source)?;
+            }
+        }
+
+        let (channels, sample_rate, bits_per_sample) = format.ok_or(WavError::Invalid)?;
+        let data = data.ok_or(WavError::Invalid)?;
+        let voice = Self::new(channels, sample_rate, bits_per_sample, data);
+        if !voice.data.len().is_multiple_of(voice.block_align()) {
+            return Err(WavError::Invalid);
+        }
+
+        Ok(voice)
+    }
+    /// Reads the contents of a WAV format chunk, returning the channel count, sample rate & bits
+    /// per sample.
+    fn read_wav_format<R: Read>(source: &mut R) -> Result<(u16, u32, u16), WavError> {
+        let format_tag = u16::from_read(source)?;
+        let channels = u16::from_read(source)?;
+        let sample_rate = u32::from_read(source)?;
+        let _byte_rate = u32::from_read(source)?;
+        let _block_align = u16::from_read(source)?;
+        let bits_per_sample = u16::from_read(source)?;
+
+        let format_tag = match format_tag {
+            // Extensible formats specify the actual format at the start of a GUID.
+            Self::WAV_FORMAT_EXTENSIBLE => {
+                let _extension_size = u16::from_read(source)?;
+                let _valid_bits = u16::from_read(source)?;
+                let _channel_mask = u32::from_read(source)?;
+                u16::from_read(source)?
+            }
+            format_tag => format_tag,
+        };
+        let voice = Self::new(channels, sample_rate, bits_per_sample, Box::new([]));
+        if (format_tag != Self::WAV_FORMAT_PCM) || !voice.is_supported() {
+            return Err(WavError::Unsupported);
+        }
+
+        Ok((channels, sample_rate, bits_per_sample))
+    }
+
+    /// Writes the voice's sample data as a RIFF WAV file. Voice parameters (key, tuning & flags)
+    /// aren't included.
+    ///
+    /// Fails with [`WavError::Invalid`] if the data ends part-way through a frame, or with
+    /// [`WavError::Oversized`] if the data or byte rate doesn't fit in 32 bits.
+    ///
+    /// ```
+    /// # use ptcrab::voice::{PcmVoice, WavError};
+    /// # use std::io::Cursor;
+    /// let voice = PcmVoice::new(2, u32::MAX / 2, 16, Box::new([]));
+    /// let result = voice.write_wav(&mut Cursor::new(vec![]));
+    /// assert!(matches!(result, Err(WavError::Oversized)));
+    ///
+    /// let voice = PcmVoice::new(2, 22050, 16, Box::new([0, 1, 2]));
+    /// let result = voice.write_wav(&mut Cursor::new(vec![]));
+    /// assert!(matches!(result, Err(WavError::Invalid)));
+    /// ```
+    pub fn write_wav<W: Write + Seek>(&self, sink: &mut W) -> Result<u64, WavError> {
+        if !self.is_supported() {
+            return Err(WavError::Unsupported);
+        }
+        if !self.data.len().is_multiple_of(self.block_align()) {
+            return Err(WavError::Invalid);
+        }
+        // Data and the chunks before it must fit within the RIFF chunk.
+        let data_size: u32 = self
+            .data
+            .len()
+            .try_into()
+            .map_err(|_| WavError::Oversized)?;
+        let riff_size = data_size
+            .checked_add(36 + (data_size % 2))
+            .ok_or(WavError::Oversized)?;
+        let block_align = self.block_align() as u16;
+        let byte_rate = self
+            .sample_rate
+            .checked_mul(block_align as u32)
+            .ok_or(WavError::Oversized)?;
+
+        let start_pos = b"RIFF".write_to(sink)?;
+        riff_size.write_to(sink)?;
+        b"WAVE".write_to(sink)?;
+
+        b"fmt ".write_to(sink)?;
+        16_u32.write_to(sink)?;
+        Self::WAV_FORMAT_PCM.write_to(sink)?;
+        self.channels.write_to(sink)?;
+        self.sample_rate.write_to(sink)?;
+        byte_rate.write_to(sink)?;
+        block_align.write_to(sink)?;
+        self.bits_per_sample.write_to(sink)?;
+
+        b"data".write_to(sink)?;
+        data_size.write_to(sink)?;
+        sink.write_all(&self.data)?;
+        // Chunks are padded to an even length.
+        if !data_size.is_multiple_of(2) {
+            0_u8.write_to(sink)?;
+        }
+
+        Ok(start_pos)
+    }
+
+    /// Converts sample data into 16-bit stereo frames at the given sample rate, the same way pxtone
+    /// prepares samples for playback. Returns [`None`] if the format is unsupported.
+    pub(crate) fn to_stereo_frames(&self, sample_rate: u32) -> Option<Box<[[i16; 2]]>> {
+        let frames: Vec<_> = match (self.channels, self.bits_per_sample) {
+            // 8-bit samples are unsigned.
+            (1, 8) => self
+                .data
+                .iter()
+                .map(|&value| [((value as i16) - 128) << 8; 2])
+                .collect(),
+            (2, 8) => self
+                .data
+                .chunks_exact(2)
+                .map(|frame| [frame[0], frame[1]].map(|value| ((value as i16) - 128) << 8))
+                .collect(),
+            (1, 16) => self
+                .data
+                .chunks_exact(2)
+                .map(|value| [i16::from_le_bytes([value[0], value[1]]); 2])
+                .collect(),
+            (2, 16) => self
+                .data
+                .chunks_exact(4)
+                .map(|frame| {
+                    [
+                        i16::from_le_bytes([frame[0], frame[1]]),
+                        i16::from_le_bytes([frame[2], frame[3]]),
+                    ]
+                })
+                .collect(),
+            _ => return None,
+        };
+
+        Some(resample_frames(&frames, self.sample_rate, sample_rate))
+    }
+}
+
+/// Resamples stereo frames using nearest-neighbour interpolation, matching pxtone's sample rate
+/// conversion.
+pub(crate) fn resample_frames(
+    frames: &[[i16; 2]],
+    from_rate: u32,
+    to_rate: u32,
+) -> Box<[[i16; 2]]> {
+    if (from_rate == to_rate) || (from_rate == 0) || frames.is_empty() {
+        return frames.into();
+    }
+
+    // pxtone rounds the converted length up, in bytes of 16-bit stereo data.
+    let (from_rate, to_rate) = (from_rate as u64, to_rate as u64);
+    let len = ((frames.len() as u64 * 4 * to_rate).div_ceil(from_rate) / 4) as usize;
+    (0..len)
+        .map(|index| {
+            let from_index = ((index as f64) * (from_rate as f64) / (to_rate as f64)) as usize;
+            frames[from_index.min(frames.len() - 1)]
+        })
+        .collect()
+}
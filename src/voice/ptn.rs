@@ -0,0 +1,43 @@
+use super::VoiceFlags;
+use crate::ptnoise::Ptnoise;
+use crate::{Key, Tuning};
+
+//--------------------------------------------------------------------------------------------------
+
+/// Project voice made from a ptnoise, which pxtone renders to PCM ahead of playback.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PtnVoice {
+    /// Key at which the rendered noise plays back at its original speed. Defaults to
+    /// A<sub>4</sub>.
+    pub inherent_key: Key,
+    /// Tuning value for this voice.
+    pub tuning: Tuning,
+    /// Voice flags.
+    pub flags: VoiceFlags,
+
+    /// Noise design.
+    pub ptnoise: Ptnoise,
+}
+
+impl PtnVoice {
+    /// Creates a voice from the given ptnoise, using default parameters.
+    pub fn new(ptnoise: Ptnoise) -> Self {
+        Self {
+            inherent_key: Key::A4,
+            tuning: Tuning::default(),
+            flags: VoiceFlags {
+                wave_loop: false,
+                smooth: true,
+                beat_fit: false,
+            },
+
+            ptnoise,
+        }
+    }
+}
+
+impl Default for PtnVoice {
+    fn default() -> Self {
+        Self::new(Ptnoise::default())
+    }
+}